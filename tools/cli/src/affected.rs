@@ -0,0 +1,253 @@
+//! Module for the `affected` command
+//!
+//! The files changed between two revisions are mapped to the features owning them, with the
+//! same boundaries as the owner lookup. Features depending on an affected feature, directly
+//! or through other features, can be added so CI runs every test suite a change may break.
+
+use anyhow::{Context, Result};
+use git2::{DiffOptions, Repository};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::Path;
+
+use crate::feature_detection::find_feature_for_path;
+use crate::models::{Feature, flatten_features};
+
+/// A feature touched by a change
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedFeature {
+    pub name: String,
+    pub path: String,
+    pub owner: String,
+    /// Changed files of the feature, relative to the scanned folder
+    pub changed_files: Vec<String>,
+    /// Affected features this feature imports, set for features only affected as dependents
+    pub depends_on: Vec<String>,
+}
+
+/// Files changed between `base` and `head`, relative to `base_path`
+///
+/// Without `head`, `base` is compared to the working directory, uncommitted and untracked
+/// files included. Both sides of a rename are returned and files outside of `base_path` are
+/// left out.
+pub fn changed_files(base_path: &Path, base: &str, head: Option<&str>) -> Result<Vec<String>> {
+    let repo = Repository::discover(base_path).with_context(|| {
+        format!(
+            "failed to discover git repository at `{}`",
+            base_path.display()
+        )
+    })?;
+
+    let base_tree = repo
+        .revparse_single(base)
+        .and_then(|object| object.peel_to_tree())
+        .with_context(|| format!("could not resolve revision `{}`", base))?;
+
+    let diff = match head {
+        Some(head) => {
+            let head_tree = repo
+                .revparse_single(head)
+                .and_then(|object| object.peel_to_tree())
+                .with_context(|| format!("could not resolve revision `{}`", head))?;
+            repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), None)?
+        }
+        None => {
+            let mut options = DiffOptions::new();
+            options.include_untracked(true).recurse_untracked_dirs(true);
+            repo.diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut options))?
+        }
+    };
+
+    let workdir = repo
+        .workdir()
+        .context("repository has no working directory")?
+        .canonicalize()?;
+    let canonical_base = base_path.canonicalize()?;
+
+    let mut files = BTreeSet::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if let Some(path) = file.path()
+                && let Ok(relative_path) = workdir.join(path).strip_prefix(&canonical_base)
+            {
+                files.insert(relative_path.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+
+    Ok(files.into_iter().collect())
+}
+
+/// Features owning the changed files, sorted by path
+///
+/// With `include_dependents`, features importing an affected feature are added until no
+/// other feature depends on the set.
+pub fn find_affected_features(
+    features: &[Feature],
+    changed_files: &[String],
+    include_dependents: bool,
+) -> Vec<AffectedFeature> {
+    let mut affected: BTreeMap<String, AffectedFeature> = BTreeMap::new();
+
+    for file in changed_files {
+        if let Some(feature) = find_feature_for_path(file, features) {
+            affected
+                .entry(feature.path.clone())
+                .or_insert_with(|| new_affected_feature(feature))
+                .changed_files
+                .push(file.clone());
+        }
+    }
+
+    if include_dependents {
+        let all_features = flatten_features(features);
+
+        let mut queue: VecDeque<String> = affected.keys().cloned().collect();
+        while let Some(target_path) = queue.pop_front() {
+            for feature in &all_features {
+                let depends_on_target = feature
+                    .dependencies
+                    .iter()
+                    .any(|dependency| dependency.feature_path == target_path);
+                if !depends_on_target {
+                    continue;
+                }
+
+                match affected.get_mut(&feature.path) {
+                    Some(dependent) => {
+                        // Features with changed files are affected regardless of their imports
+                        if dependent.changed_files.is_empty()
+                            && !dependent.depends_on.contains(&target_path)
+                        {
+                            dependent.depends_on.push(target_path.clone());
+                        }
+                    }
+                    None => {
+                        let mut dependent = new_affected_feature(feature);
+                        dependent.depends_on.push(target_path.clone());
+                        affected.insert(feature.path.clone(), dependent);
+                        queue.push_back(feature.path.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    affected.into_values().collect()
+}
+
+fn new_affected_feature(feature: &Feature) -> AffectedFeature {
+    AffectedFeature {
+        name: feature.name.clone(),
+        path: feature.path.clone(),
+        owner: feature.owner.clone(),
+        changed_files: Vec::new(),
+        depends_on: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_fixture::{commit_all, write_file};
+    use crate::models::{Dependency, DependencyType};
+    use tempfile::TempDir;
+
+    fn feature(path: &str, features: Vec<Feature>, dependencies: &[&str]) -> Feature {
+        Feature {
+            owner: "team".to_string(),
+            features,
+            dependencies: dependencies
+                .iter()
+                .map(|target| Dependency {
+                    source_filename: format!("{}/index.ts", path),
+                    target_filename: format!("{}/index.ts", target),
+                    line: 1,
+                    content: String::new(),
+                    feature_path: target.to_string(),
+                    dependency_type: DependencyType::Sibling,
+                })
+                .collect(),
+            ..Feature::at(path)
+        }
+    }
+
+    #[test]
+    fn test_changed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        let repo = Repository::init(base).unwrap();
+
+        write_file(&repo, "src/features/a/index.ts", "a");
+        write_file(&repo, "README.md", "# Project");
+        let first = commit_all(&repo, "first", 1_000);
+
+        write_file(&repo, "src/features/a/index.ts", "a2");
+        let second = commit_all(&repo, "second", 2_000);
+
+        write_file(&repo, "src/features/b/new.ts", "b");
+
+        let first = first.to_string();
+        let second = second.to_string();
+
+        assert_eq!(
+            changed_files(base, &first, Some(&second)).unwrap(),
+            vec!["src/features/a/index.ts"]
+        );
+        assert_eq!(
+            changed_files(base, &first, None).unwrap(),
+            vec!["src/features/a/index.ts", "src/features/b/new.ts"]
+        );
+        assert_eq!(
+            changed_files(&base.join("src/features/b"), &first, None).unwrap(),
+            vec!["new.ts"]
+        );
+        assert!(changed_files(base, "unknown-ref", None).is_err());
+    }
+
+    #[test]
+    fn test_find_affected_features() {
+        let features = vec![
+            feature(
+                "features/a",
+                vec![feature("features/a/features/nested", vec![], &[])],
+                &[],
+            ),
+            feature("features/b", vec![], &["features/a"]),
+            feature("features/c", vec![], &["features/b"]),
+            feature("features/d", vec![], &[]),
+        ];
+        let changed = vec![
+            "features/a/index.ts".to_string(),
+            "features/a/features/nested/x.ts".to_string(),
+            "features/d/x.ts".to_string(),
+            "shared/util.ts".to_string(),
+        ];
+
+        let paths = |affected: &[AffectedFeature]| -> Vec<String> {
+            affected.iter().map(|f| f.path.clone()).collect()
+        };
+
+        let affected = find_affected_features(&features, &changed, false);
+        assert_eq!(
+            paths(&affected),
+            vec!["features/a", "features/a/features/nested", "features/d"]
+        );
+        assert_eq!(affected[0].changed_files, vec!["features/a/index.ts"]);
+
+        let affected = find_affected_features(&features, &changed, true);
+        assert_eq!(
+            paths(&affected),
+            vec![
+                "features/a",
+                "features/a/features/nested",
+                "features/b",
+                "features/c",
+                "features/d"
+            ]
+        );
+        assert!(affected[2].changed_files.is_empty());
+        assert_eq!(affected[2].depends_on, vec!["features/a"]);
+        assert_eq!(affected[3].depends_on, vec!["features/b"]);
+    }
+}
@@ -1,31 +1,62 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
+mod affected;
 mod build;
+mod cache;
+mod changelog;
+mod check_report;
 mod checker;
 mod codeowners;
+mod commit_links;
+mod config;
+mod contributors;
+mod coupling;
 mod coverage_parser;
 mod dependency_resolver;
+mod diff;
+mod feature_detection;
 mod feature_metadata_detector;
 mod features_toml_parser;
 mod file_scanner;
+#[cfg(test)]
+mod git_fixture;
 mod git_helper;
+mod hotspots;
 mod http_server;
 mod import_detector;
 mod models;
+mod owner_suggestion;
+mod owners;
 mod printer;
 mod readme_parser;
+mod releases;
 mod scan;
+mod timeline;
+mod walker;
 
+use affected::{changed_files, find_affected_features};
 use build::{BuildConfig, create_build};
-use checker::run_checks;
+use changelog::{build_changelog, unreleased_commits};
+use check_report::ReportFormat;
+use checker::{RULES, Severity, find_rule, run_checks};
 use codeowners::generate_codeowners;
+use config::ProjectConfig;
+use coupling::collect_couplings;
+use diff::{DiffFormat, diff_features, format_markdown, format_text, read_report};
+use file_scanner::list_files_recursive_with_hotspots;
+use hotspots::build_report;
 use http_server::serve_features_with_watching;
-use models::Feature;
+use models::{Feature, flatten_features};
+use owner_suggestion::{TeamMap, suggest_owners, write_owner};
+use owners::{OwnerIndex, group_paths_by_owner};
 use printer::print_features;
+use releases::{build_releases, read_releases};
 use scan::{ScanConfig, scan_features};
+use timeline::{Sampling, build_timeline, sample_revisions};
 
 /// A CLI tool for discovering features in a folder by reading README.md or README.mdx files,
 /// and serving them via HTTP or static builds.
@@ -35,14 +66,118 @@ use scan::{ScanConfig, scan_features};
 #[command(about = env!("CARGO_PKG_DESCRIPTION"))]
 #[command(arg_required_else_help = true)]
 struct Cli {
-    /// The path to the directory to list
-    #[arg(required = false)]
-    path: Option<std::path::PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
 
     /// Print version information
     #[arg(short = 'V', long)]
     version: bool,
 
+    #[command(flatten)]
+    legacy: LegacyArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the features found in a folder
+    List(ListArgs),
+    /// Start an HTTP server to serve the features and the web dashboard
+    Serve(ServeArgs),
+    /// Create a static build with embedded files and features.json
+    Build(BuildArgs),
+    /// Run checks on features (e.g., duplicate names)
+    Check(CheckArgs),
+    /// Find the owner of a specific file or folder
+    Owner(OwnerArgs),
+    /// Find the owners of many files or folders, read one per line from stdin or a file
+    Owners(OwnersArgs),
+    /// List the features changed between two git revisions
+    Affected(AffectedArgs),
+    /// Compare two feature reports, read from features.json files or scanned from git revisions
+    Diff(DiffArgs),
+    /// Generate a changelog per feature from conventional commits
+    Changelog(ChangelogArgs),
+    /// List the features most often changed in the same commits
+    Coupling(CouplingArgs),
+    /// Rank features and files by churn multiplied by size, to target refactoring
+    Hotspots(HotspotsArgs),
+    /// List the features changed by each release (git tag), with commit types and authors
+    Releases(ReleasesArgs),
+    /// Show how the metrics of each feature changed over sampled revisions of the history
+    Timeline(TimelineArgs),
+    /// Suggest owners for features from the authors of their recent commits
+    SuggestOwners(SuggestOwnersArgs),
+    /// Generate or update CODEOWNERS file
+    Codeowners(CodeownersArgs),
+}
+
+/// Options shared by every command that scans a folder for features.
+///
+/// Unset options fall back to the `features.config.toml` file found in the scanned path
+/// or its closest ancestor.
+#[derive(Args, Clone)]
+struct ScanArgs {
+    /// The path to the directory to scan (default: current directory)
+    path: Option<PathBuf>,
+
+    /// Path to the configuration file (default: features.config.toml in the scanned path or its ancestors)
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Skip computing changes (git commits and decisions) in the output
+    #[arg(long)]
+    skip_changes: bool,
+
+    /// Path to the coverage directory (overrides default search)
+    #[arg(long)]
+    coverage_dir: Option<PathBuf>,
+
+    /// Project directory for CODEOWNERS paths and additional coverage search locations
+    #[arg(long)]
+    project_dir: Option<PathBuf>,
+
+    /// Glob of files or directories to leave out of the scan, in .gitignore syntax (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Scan files ignored by .gitignore and .ignore files
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Keep scan results in .features-cache/ so later scans only process what changed
+    #[arg(long)]
+    cache: bool,
+
+    /// Scan a git revision (commit, tag or branch) instead of the working directory
+    #[arg(long, value_name = "REV")]
+    rev: Option<String>,
+
+    /// Only read the commits made on or after this date (YYYY-MM-DD, YYYY-MM-DD HH:MM:SS or RFC 3339)
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+
+    /// Only read the commits made on or before this date
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+
+    /// Read at most this many commits, newest first
+    #[arg(long, value_name = "N")]
+    max_commits: Option<usize>,
+
+    /// Follow only the first parent of merge commits
+    #[arg(long)]
+    first_parent: bool,
+
+    /// File of author aliases in .mailmap format, applied after the repository .mailmap
+    #[arg(long, value_name = "FILE")]
+    mailmap: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
     /// Output features as JSON
     #[arg(long)]
     json: bool,
@@ -55,90 +190,476 @@ struct Cli {
     #[arg(long)]
     description: bool,
 
-    /// Display only unique list of owners
+    /// Include coverage information in the output
     #[arg(long)]
-    list_owners: bool,
+    coverage: bool,
 
-    /// Run checks on features (e.g., duplicate names)
+    /// Display only unique list of owners
     #[arg(long)]
-    check: bool,
+    owners: bool,
+}
 
-    /// Start an HTTP server to serve the features
-    #[arg(long)]
-    serve: bool,
+#[derive(Args)]
+struct ServeArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
 
-    /// Port for the HTTP server (default: 3000)
+    /// Port for the HTTP server
     #[arg(long, default_value = "3000")]
     port: u16,
+}
 
-    /// Create a static build with embedded files and features.json
-    #[arg(long)]
-    build: bool,
+#[derive(Args)]
+struct BuildArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
 
     /// Output directory for the static build
     #[arg(long, default_value = "build")]
-    build_dir: std::path::PathBuf,
+    build_dir: PathBuf,
+}
 
-    /// Skip computing changes (git commits and decisions) in the output
+#[derive(Args)]
+struct CheckArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Override the severity of a rule, e.g. `missing-owner=error` or `empty-feature=off` (repeatable)
+    #[arg(long = "rule", value_name = "ID=SEVERITY", value_parser = parse_rule_override)]
+    rules: Vec<(String, Severity)>,
+
+    /// List the available rules and their severity, then exit
     #[arg(long)]
-    skip_changes: bool,
+    list_rules: bool,
 
-    /// Find the owner of a specific file or folder
+    /// Output format of the diagnostics: text, json or sarif
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    format: ReportFormat,
+}
+
+fn parse_rule_override(value: &str) -> Result<(String, Severity)> {
+    let (id, severity) = value
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("expected ID=SEVERITY, got `{}`", value))?;
+
+    if find_rule(id).is_none() {
+        anyhow::bail!("unknown rule `{}`", id);
+    }
+
+    Ok((id.to_string(), severity.parse()?))
+}
+
+#[derive(Args)]
+struct OwnerArgs {
+    /// The file or folder to find the owner of
+    target: PathBuf,
+
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Output the owner as JSON
     #[arg(long)]
-    find_owner: Option<String>,
+    json: bool,
+}
 
-    /// Path to the coverage directory (overrides default search)
+#[derive(Args)]
+struct OwnersArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// File listing the paths to look up, one per line (default: stdin)
+    #[arg(long, value_name = "FILE")]
+    from: Option<PathBuf>,
+
+    /// Output the owners as JSON
     #[arg(long)]
-    coverage_dir: Option<std::path::PathBuf>,
+    json: bool,
+}
 
-    /// Include coverage information in the output
+#[derive(Args)]
+struct AffectedArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Revision to compare from (branch, tag or commit)
+    #[arg(long, value_name = "REF")]
+    base: String,
+
+    /// Revision to compare to (default: the working directory, uncommitted files included)
+    #[arg(long, value_name = "REF")]
+    head: Option<String>,
+
+    /// Also list the features importing an affected feature, directly or not
     #[arg(long)]
-    coverage: bool,
+    dependents: bool,
 
-    /// Generate or update CODEOWNERS file
+    /// Output the affected features as JSON
     #[arg(long)]
-    generate_codeowners: bool,
+    json: bool,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Report to compare from: a features.json file or a revision (branch, tag or commit)
+    #[arg(long, value_name = "REF|FILE")]
+    base: String,
+
+    /// Report to compare to: a features.json file or a revision (default: the working directory)
+    #[arg(long, value_name = "REF|FILE")]
+    head: Option<String>,
+
+    /// Output format: text, json or markdown
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    format: DiffFormat,
+}
 
-    /// Project directory for CODEOWNERS file generation
+#[derive(Args)]
+struct ChangelogArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Leave out the commits of this revision and its ancestors (e.g. the previous release tag)
+    #[arg(long, value_name = "REF")]
+    from: Option<String>,
+
+    /// Scan this revision and list the commits up to it (default: HEAD)
+    #[arg(long, value_name = "REF")]
+    to: Option<String>,
+
+    /// Output the changelog as JSON instead of Markdown
     #[arg(long)]
-    project_dir: Option<std::path::PathBuf>,
+    json: bool,
+}
 
-    /// Custom path and filename for CODEOWNERS file (default: CODEOWNERS)
+#[derive(Args)]
+struct CouplingArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Only list the couplings of at least this degree, in percent
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0)]
+    min_degree: f64,
+
+    /// Output the couplings as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct HotspotsArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Number of features and of files to list
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    limit: usize,
+
+    /// Output the hotspots as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct ReleasesArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Only show this release (tag)
+    #[arg(long, value_name = "TAG")]
+    release: Option<String>,
+
+    /// Only show the releases changing this feature, given by path or name
+    #[arg(long, value_name = "FEATURE")]
+    feature: Option<String>,
+
+    /// Number of releases to show, the most recent ones being kept
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    max_releases: usize,
+
+    /// Output the releases as JSON
     #[arg(long)]
-    codeowners_path: Option<std::path::PathBuf>,
+    json: bool,
+}
+
+#[derive(Args)]
+struct TimelineArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Sample every tag reachable from the scanned revision
+    #[arg(long, conflicts_with = "every")]
+    tags: bool,
+
+    /// Sample the last commit of every period of this many weeks (default: 4)
+    #[arg(long, value_name = "WEEKS")]
+    every: Option<u32>,
+
+    /// Number of revisions to sample, the most recent ones being kept
+    #[arg(long, value_name = "N", default_value_t = 12)]
+    max_samples: usize,
+
+    /// Write the timeline as JSON to this file (e.g. history.json)
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Output the timeline as JSON
+    #[arg(long, conflicts_with = "output")]
+    json: bool,
+}
+
+#[derive(Args)]
+struct SuggestOwnersArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// TOML file listing the members (emails or names) of each team, e.g. `team-a = ["alice@example.com"]`
+    #[arg(long, value_name = "FILE")]
+    teams: Option<PathBuf>,
+
+    /// Number of recent commits of each feature to look at
+    #[arg(long, value_name = "N", default_value_t = 50)]
+    recent_commits: usize,
+
+    /// Also suggest owners for features that already declare one
+    #[arg(long)]
+    all: bool,
+
+    /// Write the suggested owners into FEATURES.toml or the README front matter
+    #[arg(long)]
+    write: bool,
+
+    /// Write without asking for confirmation
+    #[arg(long, requires = "write")]
+    yes: bool,
+
+    /// Output the suggestions as JSON
+    #[arg(long, conflicts_with = "write")]
+    json: bool,
+}
+
+#[derive(Args)]
+struct CodeownersArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+
+    /// Custom path and filename for CODEOWNERS file (default: CODEOWNERS)
+    #[arg(long = "output", visible_alias = "codeowners-path")]
+    codeowners_path: Option<PathBuf>,
 
     /// Custom prefix for owner names in CODEOWNERS file (default: @)
-    #[arg(long, default_value = "@")]
-    codeowners_prefix: String,
+    #[arg(long = "prefix", visible_alias = "codeowners-prefix")]
+    codeowners_prefix: Option<String>,
+}
+
+/// Flags from before subcommands existed.
+///
+/// They are kept as hidden, deprecated aliases so that existing scripts keep working:
+/// `features <path> --serve` behaves like `features serve <path>`.
+#[derive(Args)]
+struct LegacyArgs {
+    /// The path to the directory to list
+    #[arg(required = false)]
+    path: Option<PathBuf>,
+
+    #[arg(long, hide = true)]
+    json: bool,
+
+    #[arg(long, hide = true)]
+    flat: bool,
+
+    #[arg(long, hide = true)]
+    description: bool,
+
+    #[arg(long, hide = true)]
+    list_owners: bool,
+
+    #[arg(long, hide = true)]
+    check: bool,
+
+    #[arg(long, hide = true)]
+    serve: bool,
+
+    #[arg(long, hide = true, default_value = "3000")]
+    port: u16,
+
+    #[arg(long, hide = true)]
+    build: bool,
+
+    #[arg(long, hide = true, default_value = "build")]
+    build_dir: PathBuf,
+
+    #[arg(long, hide = true)]
+    skip_changes: bool,
+
+    #[arg(long, hide = true)]
+    find_owner: Option<PathBuf>,
+
+    #[arg(long, hide = true)]
+    coverage_dir: Option<PathBuf>,
+
+    #[arg(long, hide = true)]
+    coverage: bool,
+
+    #[arg(long, hide = true)]
+    generate_codeowners: bool,
+
+    #[arg(long, hide = true)]
+    project_dir: Option<PathBuf>,
+
+    #[arg(long, hide = true)]
+    codeowners_path: Option<PathBuf>,
+
+    #[arg(long, hide = true)]
+    codeowners_prefix: Option<String>,
 }
 
-fn flatten_features(features: &[Feature]) -> Vec<Feature> {
-    let mut flat_features = Vec::new();
+impl LegacyArgs {
+    fn scan_args(&self) -> ScanArgs {
+        ScanArgs {
+            path: self.path.clone(),
+            config: None,
+            skip_changes: self.skip_changes,
+            coverage_dir: self.coverage_dir.clone(),
+            project_dir: self.project_dir.clone(),
+            exclude: Vec::new(),
+            no_ignore: false,
+            cache: false,
+            rev: None,
+            since: None,
+            until: None,
+            max_commits: None,
+            first_parent: false,
+            mailmap: None,
+        }
+    }
 
-    for feature in features {
-        // Create a flattened version of this feature (without nested features)
-        let flat_feature = Feature {
+    /// Translate the deprecated flags into the subcommands they stand for.
+    ///
+    /// Modes keep the precedence they always had: `--find-owner` wins over everything, then
+    /// `--serve`, `--build` and `--check`. `--generate-codeowners` runs along with any of those
+    /// but `--find-owner`, and wins over `--list-owners`. Flags left out by this precedence are
+    /// reported on stderr.
+    fn into_commands(self) -> Vec<Command> {
+        let mut modes = Vec::new();
+        for (flag, enabled) in [
+            ("--find-owner", self.find_owner.is_some()),
+            ("--serve", self.serve),
+            ("--build", self.build),
+            ("--check", self.check),
+            ("--generate-codeowners", self.generate_codeowners),
+            ("--list-owners", self.list_owners),
+        ] {
+            if enabled {
+                modes.push(flag);
+            }
+        }
+
+        for flag in &modes {
+            eprintln!(
+                "Warning: {} is deprecated, use 'features {}' instead.",
+                flag,
+                match *flag {
+                    "--find-owner" => "owner <target>",
+                    "--serve" => "serve",
+                    "--build" => "build",
+                    "--check" => "check",
+                    "--generate-codeowners" => "codeowners",
+                    _ => "list --owners",
+                }
+            );
+        }
+
+        let applied = match modes.first().copied() {
+            Some(first @ ("--find-owner" | "--generate-codeowners")) => vec![first],
+            Some(first) if self.generate_codeowners => vec!["--generate-codeowners", first],
+            Some(first) => vec![first],
+            None => Vec::new(),
+        };
+        let ignored: Vec<&str> = modes
+            .iter()
+            .filter(|flag| !applied.contains(flag))
+            .copied()
+            .collect();
+        if !ignored.is_empty() {
+            eprintln!(
+                "Warning: {} ignored in favor of {}, run one subcommand per invocation instead (see 'features --help').",
+                ignored.join(" and "),
+                applied.join(" and ")
+            );
+        }
+
+        let scan = self.scan_args();
+
+        if let Some(target) = self.find_owner {
+            return vec![Command::Owner(OwnerArgs {
+                target,
+                scan,
+                json: self.json,
+            })];
+        }
+
+        let mut commands = Vec::new();
+        if self.generate_codeowners {
+            commands.push(Command::Codeowners(CodeownersArgs {
+                scan: scan.clone(),
+                codeowners_path: self.codeowners_path,
+                codeowners_prefix: self.codeowners_prefix,
+            }));
+        }
+
+        if self.serve {
+            commands.push(Command::Serve(ServeArgs {
+                scan,
+                port: self.port,
+            }));
+        } else if self.build {
+            commands.push(Command::Build(BuildArgs {
+                scan,
+                build_dir: self.build_dir,
+            }));
+        } else if self.check {
+            commands.push(Command::Check(CheckArgs {
+                scan,
+                rules: Vec::new(),
+                list_rules: false,
+                format: ReportFormat::Text,
+            }));
+        } else if !self.generate_codeowners {
+            commands.push(Command::List(ListArgs {
+                scan,
+                json: self.json,
+                flat: self.flat,
+                description: self.description,
+                coverage: self.coverage,
+                owners: self.list_owners,
+            }));
+        }
+
+        commands
+    }
+}
+
+/// Every feature of a tree without its nested features, changes nor decisions
+fn flat_features(features: &[Feature]) -> Vec<Feature> {
+    flatten_features(features)
+        .into_iter()
+        .map(|feature| Feature {
             name: feature.name.clone(),
             description: feature.description.clone(),
             owner: feature.owner.clone(),
             is_owner_inherited: feature.is_owner_inherited,
             path: feature.path.clone(),
-            features: Vec::new(), // Empty for flat structure
+            features: Vec::new(),
             meta: feature.meta.clone(),
             changes: Vec::new(),
             decisions: Vec::new(),
             stats: feature.stats.clone(),
             dependencies: feature.dependencies.clone(),
-        };
-
-        flat_features.push(flat_feature);
-
-        // Recursively flatten nested features
-        let nested_flat = flatten_features(&feature.features);
-        flat_features.extend(nested_flat);
-    }
-
-    flat_features
+        })
+        .collect()
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -155,63 +676,15 @@ fn find_owner_for_path(
     features: &[Feature],
     base_path: &std::path::Path,
 ) -> Option<OwnerInfo> {
-    // Canonicalize the target path
-    let canonical_target = std::fs::canonicalize(target_path).ok()?;
-
-    // Helper function to recursively search for the closest feature
-    fn find_closest_feature(
-        target: &std::path::Path,
-        features: &[Feature],
-        _parent_owner: Option<&str>,
-        base_path: &std::path::Path,
-    ) -> Option<OwnerInfo> {
-        let mut best_match: Option<OwnerInfo> = None;
-        let mut best_match_depth = usize::MAX;
-
-        for feature in features {
-            let feature_path = base_path.join(&feature.path);
-            if let Ok(canonical_feature_path) = std::fs::canonicalize(&feature_path) {
-                // Check if target is within this feature's directory
-                if target.starts_with(&canonical_feature_path) {
-                    // Calculate depth (how many components between feature and target)
-                    let depth = target
-                        .strip_prefix(&canonical_feature_path)
-                        .ok()
-                        .map(|p| p.components().count())
-                        .unwrap_or(usize::MAX);
-
-                    // Check nested features first
-                    if let Some(nested_match) = find_closest_feature(
-                        target,
-                        &feature.features,
-                        Some(&feature.owner),
-                        base_path,
-                    ) {
-                        if best_match.is_none() || depth < best_match_depth {
-                            best_match = Some(nested_match);
-                            best_match_depth = depth;
-                        }
-                    } else {
-                        // This is the closest feature so far
-                        if depth < best_match_depth {
-                            let owner = OwnerInfo {
-                                owner: feature.owner.clone(),
-                                inherited: feature.is_owner_inherited,
-                                feature_name: feature.name.clone(),
-                                feature_path: feature.path.clone(),
-                            };
-                            best_match = Some(owner);
-                            best_match_depth = depth;
-                        }
-                    }
-                }
-            }
-        }
+    let index = OwnerIndex::new(features, base_path).ok()?;
+    let feature = index.find(target_path)?;
 
-        best_match
-    }
-
-    find_closest_feature(&canonical_target, features, None, base_path)
+    Some(OwnerInfo {
+        owner: feature.owner.clone(),
+        inherited: feature.is_owner_inherited,
+        feature_name: feature.name.clone(),
+        feature_path: feature.path.clone(),
+    })
 }
 
 /// Add coverage data from .coverage and coverage directories to features
@@ -257,192 +730,624 @@ fn extract_unique_owners(features: &[Feature]) -> Vec<String> {
     owners
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Cli::parse();
+/// Resolve the scanned path and its configuration, command line options taking
+/// precedence over the configuration file.
+fn resolve_scan(args: &ScanArgs) -> Result<(PathBuf, ProjectConfig)> {
+    let path = match &args.path {
+        Some(p) => p.clone(),
+        None => std::env::current_dir()?,
+    };
 
-    // Handle version flag
-    if args.version {
-        println!("{}", env!("CARGO_PKG_VERSION"));
+    let mut config = match &args.config {
+        Some(config_path) => ProjectConfig::load(config_path)?,
+        None => ProjectConfig::discover(&path)?,
+    };
+
+    if args.skip_changes {
+        config.skip_changes = Some(true);
+    }
+    if let Some(ref coverage_dir) = args.coverage_dir {
+        config.coverage_dir = Some(coverage_dir.clone());
+    }
+    if let Some(ref project_dir) = args.project_dir {
+        config.project_dir = Some(project_dir.clone());
+    }
+    config.scan.exclude.extend(args.exclude.iter().cloned());
+    if args.no_ignore {
+        config.scan.respect_gitignore = false;
+    }
+    if args.cache {
+        config.scan.cache = true;
+    }
+    config.scan.revision = args.rev.clone();
+
+    let history = &mut config.scan.history;
+    if args.since.is_some() {
+        history.since = args.since.clone();
+    }
+    if args.until.is_some() {
+        history.until = args.until.clone();
+    }
+    if args.max_commits.is_some() {
+        history.max_commits = args.max_commits;
+    }
+    if args.first_parent {
+        history.first_parent = true;
+    }
+    if args.mailmap.is_some() {
+        history.mailmap = args.mailmap.clone();
+    }
+    history.validate()?;
+
+    Ok((path, config))
+}
+
+/// Scan the features of a folder with the resolved configuration.
+fn scan(path: &Path, config: &ProjectConfig, with_coverage: bool) -> Result<Vec<Feature>> {
+    let current_dir = std::env::current_dir()?;
+
+    let mut scan_config = ScanConfig::new(&current_dir)
+        .skip_changes(config.skip_changes.unwrap_or(false))
+        .with_coverage(with_coverage)
+        .settings(config.scan.clone());
+
+    if let Some(ref coverage_dir) = config.coverage_dir {
+        scan_config = scan_config.coverage_dir(coverage_dir);
+    }
+
+    if let Some(ref project_dir) = config.project_dir {
+        scan_config = scan_config.project_dir(project_dir);
+    }
+
+    let progress = scan_progress();
+    let features = scan_features(path, scan_config.progress(progress.clone()));
+    progress.finish_and_clear();
+
+    features
+}
+
+/// Progress of the files read by a scan, only drawn when stderr is a terminal
+fn scan_progress() -> ProgressBar {
+    ProgressBar::new(0).with_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} Reading files {pos}/{len} {wide_bar}")
+            .unwrap(),
+    )
+}
+
+fn run_list(args: ListArgs) -> Result<()> {
+    let (path, config) = resolve_scan(&args.scan)?;
+    let features = scan(&path, &config, args.json || args.coverage)?;
+
+    if args.owners {
+        let unique_owners = extract_unique_owners(&features);
+
+        if args.json {
+            let json = serde_json::to_string_pretty(&unique_owners)?;
+            println!("{}", json);
+        } else {
+            eprintln!("Unique owners found in {}:", path.display());
+            for owner in unique_owners {
+                println!("{}", owner);
+            }
+        }
+        return Ok(());
+    }
+
+    let output_features = if args.flat {
+        flat_features(&features)
+    } else {
+        features
+    };
+
+    if args.json {
+        let json = serde_json::to_string_pretty(&output_features)?;
+        println!("{}", json);
+    } else {
+        eprintln!("Features found in {}:", path.display());
+        if output_features.is_empty() {
+            eprintln!("No features found.");
+        } else {
+            print_features(&output_features, 0, args.description);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_serve(args: ServeArgs) -> Result<()> {
+    let (path, config) = resolve_scan(&args.scan)?;
+    let features = scan(&path, &config, true)?;
+
+    // Show a spinner while the server is starting
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} {msg}")
+            .unwrap()
+            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
+    );
+    pb.set_message(format!(
+        "Starting Features CLI v{}...",
+        env!("CARGO_PKG_VERSION")
+    ));
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+
+    pb.set_message("Server is starting...");
+    let pb_clone = pb.clone();
+    serve_features_with_watching(
+        &features,
+        args.port,
+        path,
+        Some(Box::new(move || {
+            pb_clone.finish_and_clear();
+        })),
+        config.skip_changes.unwrap_or(false),
+        config.scan,
+    )
+    .await
+}
+
+async fn run_build(args: BuildArgs) -> Result<()> {
+    let (path, config) = resolve_scan(&args.scan)?;
+    let features = scan(&path, &config, true)?;
+    let build_config = BuildConfig::new(args.build_dir);
+    create_build(
+        &features,
+        build_config,
+        config.skip_changes.unwrap_or(false),
+    )
+    .await
+}
+
+fn run_check(args: CheckArgs) -> Result<()> {
+    let (path, mut config) = resolve_scan(&args.scan)?;
+    config.check.rules.extend(args.rules);
+
+    if args.list_rules {
+        for rule in RULES {
+            println!(
+                "{:<24} {:<8} {}",
+                rule.id,
+                rule.severity(&config.check),
+                rule.description
+            );
+        }
         return Ok(());
     }
 
-    // Handle find-owner flag - this requires a target path but not the base path
-    if let Some(target_file) = &args.find_owner {
-        let target_path = std::path::PathBuf::from(target_file);
+    let features = scan(&path, &config, false)?;
+    run_checks(&features, &path, &config.check, args.format)
+}
+
+fn run_owner(args: OwnerArgs) -> Result<()> {
+    if !args.target.exists() {
+        eprintln!("Error: Path '{}' does not exist.", args.target.display());
+        std::process::exit(1);
+    }
+
+    let (base_path, mut config) = resolve_scan(&args.scan)?;
+    // Ownership does not depend on the git history
+    config.skip_changes = Some(true);
+    let features = scan(&base_path, &config, false)?;
 
-        if !target_path.exists() {
-            eprintln!("Error: Path '{}' does not exist.", target_file);
+    match find_owner_for_path(&args.target, &features, &base_path) {
+        Some(owner_info) => {
+            if args.json {
+                let json = serde_json::to_string_pretty(&owner_info)?;
+                println!("{}", json);
+            } else {
+                println!(
+                    "Owner: {}{}",
+                    owner_info.owner,
+                    if owner_info.inherited {
+                        " (inherited)"
+                    } else {
+                        ""
+                    }
+                );
+                println!("Feature: {}", owner_info.feature_name);
+                println!("Feature Path: {}", owner_info.feature_path);
+            }
+        }
+        None => {
+            eprintln!("No feature found for path: {}", args.target.display());
             std::process::exit(1);
         }
+    }
 
-        // Determine the base path to scan for features
-        let base_path = if let Some(p) = &args.path {
-            p.clone()
-        } else {
-            // Use current directory or try to find a suitable base
-            std::env::current_dir()?
-        };
+    Ok(())
+}
+
+fn run_owners(args: OwnersArgs) -> Result<()> {
+    let input = match &args.from {
+        Some(file) => std::fs::read_to_string(file)
+            .with_context(|| format!("could not read path list `{}`", file.display()))?,
+        None => std::io::read_to_string(std::io::stdin())?,
+    };
+    let paths: Vec<String> = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
 
-        let current_dir = std::env::current_dir()?;
-        let config = ScanConfig::new(&current_dir).skip_changes(args.skip_changes);
+    let (base_path, mut config) = resolve_scan(&args.scan)?;
+    // Ownership does not depend on the git history
+    config.skip_changes = Some(true);
+    let features = scan(&base_path, &config, false)?;
 
-        let features = scan_features(&base_path, config)?;
+    // Paths are read as printed by git, from the repository root
+    let index = OwnerIndex::new(&features, &base_path)?.relative_to_repository_root()?;
+    let report = group_paths_by_owner(&index, &paths);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
 
-        match find_owner_for_path(&target_path, &features, &base_path) {
-            Some(owner_info) => {
-                if args.json {
-                    let json = serde_json::to_string_pretty(&owner_info)?;
-                    println!("{}", json);
+    for group in &report.owners {
+        println!("{}", group.owner);
+        for path_owner in &group.paths {
+            println!(
+                "  {} ({}{})",
+                path_owner.path,
+                path_owner.feature_name.as_deref().unwrap_or_default(),
+                if path_owner.inherited {
+                    ", inherited"
                 } else {
-                    println!(
-                        "Owner: {}{}",
-                        owner_info.owner,
-                        if owner_info.inherited {
-                            " (inherited)"
-                        } else {
-                            ""
-                        }
-                    );
-                    println!("Feature: {}", owner_info.feature_name);
-                    println!("Feature Path: {}", owner_info.feature_path);
+                    ""
                 }
-            }
-            None => {
-                eprintln!("No feature found for path: {}", target_file);
-                std::process::exit(1);
+            );
+        }
+    }
+    if !report.unowned.is_empty() {
+        println!("(no owner)");
+        for path_owner in &report.unowned {
+            match &path_owner.feature_name {
+                Some(feature_name) => println!("  {} ({})", path_owner.path, feature_name),
+                None => println!("  {}", path_owner.path),
             }
         }
+    }
 
-        return Ok(());
+    Ok(())
+}
+
+fn run_affected(args: AffectedArgs) -> Result<()> {
+    let (path, mut config) = resolve_scan(&args.scan)?;
+    // Only the feature boundaries and the dependencies are needed
+    config.skip_changes = Some(true);
+    // The features are those of the head revision, the working directory by default
+    if args.head.is_some() {
+        config.scan.revision = args.head.clone();
     }
 
-    // If no path is provided and no special flags are used, show help
-    let path = match args.path {
-        Some(p) => p,
-        None => {
-            // If we get here, it means no version/help flag was used
-            // This shouldn't happen with arg_required_else_help, but just in case
-            eprintln!("Error: The path argument is required.");
-            eprintln!("Try 'features --help' for more information.");
-            std::process::exit(1);
+    let changed_files = changed_files(&path, &args.base, args.head.as_deref())?;
+    let features = scan(&path, &config, false)?;
+    let affected = find_affected_features(&features, &changed_files, args.dependents);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&affected)?);
+    } else if affected.is_empty() {
+        eprintln!("No features affected.");
+    } else {
+        // One path per line so the list can be piped to other tools
+        for feature in &affected {
+            println!("{}", feature.path);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_diff(args: DiffArgs) -> Result<()> {
+    // The history is not compared, only used to find the features that moved
+    let (path, config) = resolve_scan(&args.scan)?;
+
+    let old = read_or_scan_report(&path, &config, Some(&args.base))?;
+    let new = read_or_scan_report(&path, &config, args.head.as_deref())?;
+    let diff = diff_features(&old, &new);
+
+    match args.format {
+        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+        DiffFormat::Markdown => print!("{}", format_markdown(&diff)),
+        DiffFormat::Text if diff.is_empty() => eprintln!("No differences."),
+        DiffFormat::Text => print!("{}", format_text(&diff)),
+    }
+
+    Ok(())
+}
+
+/// Features of a features.json file, of a revision or of the working directory when `None`
+fn read_or_scan_report(
+    path: &Path,
+    config: &ProjectConfig,
+    report: Option<&str>,
+) -> Result<Vec<Feature>> {
+    match report {
+        Some(file) if Path::new(file).is_file() => read_report(Path::new(file)),
+        Some(revision) => {
+            let mut config = config.clone();
+            config.scan.revision = Some(revision.to_string());
+            // Coverage reports only describe the working directory
+            scan(path, &config, false)
         }
+        None => scan(path, config, true),
+    }
+}
+
+fn run_changelog(args: ChangelogArgs) -> Result<()> {
+    let (path, mut config) = resolve_scan(&args.scan)?;
+    config.skip_changes = Some(false);
+    if args.to.is_some() {
+        config.scan.revision = args.to.clone();
+    }
+
+    let unreleased = match args.from.as_deref() {
+        Some(from) => Some(unreleased_commits(&path, from, args.to.as_deref())?),
+        None => None,
     };
+    let features = scan(&path, &config, false)?;
+    let changelogs = build_changelog(&features, unreleased.as_ref());
 
-    // Handle serve flag - show spinner while loading
-    let spinner = if args.serve {
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap()
-                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&changelogs)?);
+    } else {
+        print!(
+            "{}",
+            changelog::format_markdown(&changelogs, args.from.as_deref(), args.to.as_deref())
         );
-        pb.set_message(format!(
-            "Starting Features CLI v{}...",
-            env!("CARGO_PKG_VERSION")
-        ));
-        pb.enable_steady_tick(std::time::Duration::from_millis(100));
-        Some(pb)
+    }
+
+    Ok(())
+}
+
+fn run_coupling(args: CouplingArgs) -> Result<()> {
+    let (path, mut config) = resolve_scan(&args.scan)?;
+    config.skip_changes = Some(false);
+
+    let features = scan(&path, &config, false)?;
+    let couplings = collect_couplings(&features, args.min_degree);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&couplings)?);
+    } else if couplings.is_empty() {
+        eprintln!("No features changed together.");
     } else {
-        None
-    };
+        print!("{}", coupling::format_text(&couplings));
+    }
 
-    // Build scan configuration
-    let current_dir = std::env::current_dir()?;
-    let should_add_coverage = args.serve || args.build || args.json || args.coverage;
+    Ok(())
+}
 
-    let mut config = ScanConfig::new(&current_dir)
-        .skip_changes(args.skip_changes)
-        .with_coverage(should_add_coverage);
+fn run_hotspots(args: HotspotsArgs) -> Result<()> {
+    let (path, config) = resolve_scan(&args.scan)?;
 
-    if let Some(ref coverage_dir) = args.coverage_dir {
-        config = config.coverage_dir(coverage_dir);
+    // Every file of the scanned folder is ranked, not only the files of features
+    let progress = scan_progress();
+    let scanned = list_files_recursive_with_hotspots(&path, &config.scan, &progress);
+    progress.finish_and_clear();
+    let (features, files) = scanned?;
+    let report = build_report(&features, files, args.limit);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", hotspots::format_text(&report));
     }
 
-    if let Some(ref project_dir) = args.project_dir {
-        config = config.project_dir(project_dir);
-    }
-
-    let features = scan_features(&path, config)?;
-
-    // Generate CODEOWNERS file if requested
-    if args.generate_codeowners {
-        let output_dir = args.project_dir.as_deref().unwrap_or(&current_dir);
-        generate_codeowners(
-            &features,
-            &path,
-            args.project_dir.as_deref(),
-            output_dir,
-            args.codeowners_path.as_deref(),
-            &args.codeowners_prefix,
-        )?;
-    }
-
-    // Handle main actions - these can be combined with generate-codeowners
-    if args.serve {
-        if let Some(pb) = spinner {
-            pb.set_message("Server is starting...");
-            let pb_clone = pb.clone();
-            serve_features_with_watching(
-                &features,
-                args.port,
-                path.clone(),
-                Some(Box::new(move || {
-                    pb_clone.finish_and_clear();
-                })),
-                args.skip_changes,
-            )
-            .await?;
-        } else {
-            serve_features_with_watching(
-                &features,
-                args.port,
-                path.clone(),
-                None,
-                args.skip_changes,
-            )
-            .await?;
+    Ok(())
+}
+
+fn run_releases(args: ReleasesArgs) -> Result<()> {
+    let (path, mut config) = resolve_scan(&args.scan)?;
+    config.skip_changes = Some(false);
+
+    let tags = read_releases(&path, config.scan.revision.as_deref())?;
+    if tags.is_empty() {
+        anyhow::bail!("no tag found in the history of the scanned revision");
+    }
+    let features = scan(&path, &config, false)?;
+
+    let mut releases = build_releases(&features, tags);
+    if let Some(release) = &args.release {
+        releases.retain(|candidate| &candidate.name == release);
+        if releases.is_empty() {
+            anyhow::bail!("no release tagged `{}`", release);
         }
-    } else if args.build {
-        let build_config = BuildConfig::new(args.build_dir);
-        create_build(&features, build_config, args.skip_changes).await?;
-    } else if args.check {
-        run_checks(&features)?;
-    } else if args.generate_codeowners {
-        // If only generate-codeowners flag is set, we've already done the work above
-        // No additional output needed
-    } else if args.list_owners {
-        let unique_owners = extract_unique_owners(&features);
+    }
+    if let Some(feature) = &args.feature {
+        for release in &mut releases {
+            release
+                .features
+                .retain(|impact| &impact.path == feature || &impact.name == feature);
+        }
+        // The releases that did not change the feature are not counted toward the limit
+        releases.retain(|release| !release.features.is_empty());
+    }
+    releases.truncate(args.max_releases);
 
-        if args.json {
-            let json = serde_json::to_string_pretty(&unique_owners)?;
-            println!("{}", json);
-        } else {
-            eprintln!("Unique owners found in {}:", path.display());
-            for owner in unique_owners {
-                println!("{}", owner);
-            }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&releases)?);
+    } else {
+        print!("{}", releases::format_text(&releases));
+    }
+
+    Ok(())
+}
+
+fn run_timeline(args: TimelineArgs) -> Result<()> {
+    let (path, mut config) = resolve_scan(&args.scan)?;
+    // The metrics sampled do not need the commits of each revision
+    config.skip_changes = Some(true);
+
+    let sampling = match args.every {
+        Some(weeks) => Sampling::Weeks(weeks),
+        None if args.tags => Sampling::Tags,
+        None => Sampling::Weeks(4),
+    };
+    let revisions = sample_revisions(
+        &path,
+        config.scan.revision.as_deref(),
+        sampling,
+        args.max_samples,
+    )?;
+    if revisions.is_empty() {
+        anyhow::bail!("no revision to sample");
+    }
+
+    let mut scans = Vec::new();
+    for revision in revisions {
+        config.scan.revision = Some(revision.commit.clone());
+        match scan(&path, &config, false) {
+            Ok(features) => scans.push((revision, features)),
+            // The scanned folder may not exist yet in older revisions
+            Err(err) => eprintln!("Skipping revision {}: {:#}", revision.name, err),
         }
+    }
+    let timeline = build_timeline(scans);
+
+    if let Some(output) = &args.output {
+        std::fs::write(output, serde_json::to_string_pretty(&timeline)?)
+            .with_context(|| format!("could not write `{}`", output.display()))?;
+        eprintln!("Timeline written to {}", output.display());
+    } else if args.json {
+        println!("{}", serde_json::to_string_pretty(&timeline)?);
     } else {
-        let output_features = if args.flat {
-            flatten_features(&features)
-        } else {
-            features
+        print!("{}", timeline::format_text(&timeline));
+    }
+
+    Ok(())
+}
+
+fn run_suggest_owners(args: SuggestOwnersArgs) -> Result<()> {
+    let (path, mut config) = resolve_scan(&args.scan)?;
+    if args.write && config.scan.revision.is_some() {
+        anyhow::bail!("--write cannot be used when scanning a revision");
+    }
+    if args.recent_commits == 0 {
+        anyhow::bail!("--recent-commits must be at least 1");
+    }
+    config.skip_changes = Some(false);
+
+    let teams = args.teams.as_deref().map(TeamMap::load).transpose()?;
+    let features = scan(&path, &config, false)?;
+    let suggestions = suggest_owners(&features, teams.as_ref(), args.recent_commits, args.all);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&suggestions)?);
+        return Ok(());
+    }
+
+    if suggestions.is_empty() {
+        eprintln!("No owner to suggest.");
+        return Ok(());
+    }
+
+    for suggestion in &suggestions {
+        let current_owner = match suggestion.current_owner.as_str() {
+            "" => "no owner".to_string(),
+            owner if suggestion.is_owner_inherited => format!("{} (inherited)", owner),
+            owner => owner.to_string(),
         };
+        println!(
+            "{}: {} ({}/{} recent commits), currently {}",
+            suggestion.path,
+            suggestion.suggested_owner,
+            suggestion.commits,
+            suggestion.recent_commits,
+            current_owner
+        );
+    }
 
-        if args.json {
-            let json = serde_json::to_string_pretty(&output_features)?;
-            println!("{}", json);
-        } else {
-            eprintln!("Features found in {}:", path.display());
-            if output_features.is_empty() {
-                eprintln!("No features found.");
-            } else {
-                print_features(&output_features, 0, args.description);
-            }
+    if !args.write {
+        return Ok(());
+    }
+
+    if !args.yes {
+        eprint!("Write {} owner(s)? [y/N] ", suggestions.len());
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            eprintln!("Nothing written.");
+            return Ok(());
         }
     }
 
+    for suggestion in &suggestions {
+        let written = write_owner(&path.join(&suggestion.path), &suggestion.suggested_owner)?;
+        eprintln!("Wrote {}", written.display());
+    }
+
+    Ok(())
+}
+
+fn run_codeowners(args: CodeownersArgs) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let (path, config) = resolve_scan(&args.scan)?;
+    let features = scan(&path, &config, false)?;
+    let output_dir = config.project_dir.as_deref().unwrap_or(&current_dir);
+    let codeowners_path = args.codeowners_path.or(config.codeowners.path.clone());
+    let codeowners_prefix = args
+        .codeowners_prefix
+        .or(config.codeowners.prefix.clone())
+        .unwrap_or_else(|| "@".to_string());
+
+    generate_codeowners(
+        &features,
+        &path,
+        config.project_dir.as_deref(),
+        output_dir,
+        codeowners_path.as_deref(),
+        &codeowners_prefix,
+    )
+}
+
+async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::List(args) => run_list(args),
+        Command::Serve(args) => run_serve(args).await,
+        Command::Build(args) => run_build(args).await,
+        Command::Check(args) => run_check(args),
+        Command::Owner(args) => run_owner(args),
+        Command::Owners(args) => run_owners(args),
+        Command::Affected(args) => run_affected(args),
+        Command::Diff(args) => run_diff(args),
+        Command::Changelog(args) => run_changelog(args),
+        Command::Coupling(args) => run_coupling(args),
+        Command::Hotspots(args) => run_hotspots(args),
+        Command::Releases(args) => run_releases(args),
+        Command::Timeline(args) => run_timeline(args),
+        Command::SuggestOwners(args) => run_suggest_owners(args),
+        Command::Codeowners(args) => run_codeowners(args),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Cli::parse();
+
+    // Handle version flag
+    if args.version {
+        println!("{}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+
+    if let Some(command) = args.command {
+        return run(command).await;
+    }
+
+    // Without a subcommand, the path is mandatory unless only the owner of a file is requested
+    if args.legacy.path.is_none() && args.legacy.find_owner.is_none() {
+        eprintln!("Error: The path argument is required.");
+        eprintln!("Try 'features --help' for more information.");
+        std::process::exit(1);
+    }
+
+    for command in args.legacy.into_commands() {
+        run(command).await?;
+    }
+
     Ok(())
 }
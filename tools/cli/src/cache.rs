@@ -0,0 +1,358 @@
+//! Module for the on-disk cache of incremental scans
+//!
+//! With `cache` enabled in the `[scan]` settings (or `--cache`), what a scan learns is saved
+//! in `.features-cache/` inside the scanned folder so the next scan only redoes the work for
+//! what changed:
+//! - the line count, TODO count, imports and metadata comments of every file, invalidated
+//!   when the size or the modification time of the file changes,
+//! - the git history with the commit it was read up to, so only newer commits are walked.
+//!
+//! The cache is discarded when it was written by another version of the tool, and scans of
+//! a git revision (`--rev`) neither read nor write it.
+
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+use crate::feature_detection::{has_feature_flag, is_readme_file};
+use crate::feature_metadata_detector::{FeatureMetadataComment, extract_feature_metadata};
+use crate::git_helper::CommitHistory;
+use crate::import_detector::{ImportStatement, extract_imports};
+use crate::walker::{FileTree, Source};
+
+/// Name of the cache directory created in the scanned folder
+pub const CACHE_DIR_NAME: &str = ".features-cache";
+
+const CACHE_FILE_NAME: &str = "cache.json";
+
+/// What a scan needs to know about the content of a file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileSummary {
+    pub lines: usize,
+    pub todos: usize,
+    pub imports: Vec<ImportStatement>,
+    pub metadata: Vec<FeatureMetadataComment>,
+    /// Whether the file is a README with `feature: true` in its front matter
+    #[serde(default)]
+    pub feature_flag: bool,
+}
+
+impl FileSummary {
+    fn read(source: &Source, path: &Path) -> Self {
+        // Files that are not valid UTF-8 count as empty
+        let Ok(content) = source.read_to_string(path) else {
+            return Self::default();
+        };
+
+        Self {
+            lines: content.lines().count(),
+            // Look for TODO in comments (case-insensitive)
+            todos: content
+                .lines()
+                .filter(|line| line.to_uppercase().contains("TODO"))
+                .count(),
+            imports: extract_imports(path, &content),
+            metadata: extract_feature_metadata(path, &content),
+            feature_flag: is_readme_file(path) && has_feature_flag(&content),
+        }
+    }
+}
+
+/// Size and modification time of a file when it was summarized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+    size: u64,
+    modified_secs: u64,
+    modified_nanos: u32,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+        Some(Self {
+            size: metadata.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileEntry {
+    /// Only known when the cache is saved
+    stamp: Option<FileStamp>,
+    summary: FileSummary,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheData {
+    version: String,
+    /// File entries keyed by path relative to the scanned folder
+    files: HashMap<String, FileEntry>,
+    history: Option<CommitHistory>,
+}
+
+/// Summaries of files and git history shared by every pass of a scan
+///
+/// Every file is read at most once per scan, whether the cache is persisted or not.
+pub struct ScanCache<'a> {
+    tree: &'a FileTree,
+    /// Directory the cache is saved in, `None` when it only lives for the scan
+    dir: Option<PathBuf>,
+    /// Entries of the previous scan
+    previous_files: HashMap<String, FileEntry>,
+    /// Entries used by this scan, the only ones saved
+    files: Mutex<HashMap<String, FileEntry>>,
+    history: Option<CommitHistory>,
+}
+
+impl<'a> ScanCache<'a> {
+    /// A cache that is never saved
+    pub fn in_memory(tree: &'a FileTree) -> Self {
+        Self {
+            tree,
+            dir: None,
+            previous_files: HashMap::new(),
+            files: Mutex::new(HashMap::new()),
+            history: None,
+        }
+    }
+
+    /// Load the cache of the scanned folder, starting empty when there is none or it is unreadable
+    pub fn load(tree: &'a FileTree) -> Self {
+        let dir = tree.root().join(CACHE_DIR_NAME);
+
+        let data = fs::read_to_string(dir.join(CACHE_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheData>(&content).ok())
+            .filter(|data| data.version == env!("CARGO_PKG_VERSION"))
+            .unwrap_or_default();
+
+        Self {
+            tree,
+            dir: Some(dir),
+            previous_files: data.files,
+            files: Mutex::new(HashMap::new()),
+            history: data.history,
+        }
+    }
+
+    /// Summary of a file, read again only when it changed since it was cached
+    pub fn file_summary(&self, path: &Path) -> FileSummary {
+        let key = path
+            .strip_prefix(self.tree.root())
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+
+        if let Some(entry) = self.files.lock().unwrap().get(&key) {
+            return entry.summary.clone();
+        }
+
+        let stamp = self.dir.as_ref().and_then(|_| FileStamp::of(path));
+        let summary = match self.previous_files.get(&key) {
+            Some(entry) if stamp.is_some() && entry.stamp == stamp => {
+                let mut summary = entry.summary.clone();
+                // The scanned folder may be given as another path to the same directory
+                let file_path = path.to_string_lossy().to_string();
+                for import in &mut summary.imports {
+                    import.file_path = file_path.clone();
+                }
+                for comment in &mut summary.metadata {
+                    comment.file_path = file_path.clone();
+                }
+                summary
+            }
+            _ => FileSummary::read(self.tree.source(), path),
+        };
+
+        self.files.lock().unwrap().insert(
+            key,
+            FileEntry {
+                stamp,
+                summary: summary.clone(),
+            },
+        );
+
+        summary
+    }
+
+    /// Summarize files on every available core, so later passes never wait on the disk
+    pub fn summarize_all(&self, files: &[&Path], progress: &ProgressBar) {
+        progress.set_length(files.len() as u64);
+
+        let next = AtomicUsize::new(0);
+        let threads = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .min(files.len());
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| {
+                    while let Some(path) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        self.file_summary(path);
+                        progress.inc(1);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Take the cached git history, to be read again from where it stopped
+    pub fn take_history(&mut self) -> Option<CommitHistory> {
+        self.history.take()
+    }
+
+    pub fn set_history(&mut self, history: CommitHistory) {
+        self.history = Some(history);
+    }
+
+    /// Write the cache to disk, does nothing for an in-memory cache
+    pub fn save(self) -> Result<()> {
+        let Some(dir) = self.dir else {
+            return Ok(());
+        };
+
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("could not create cache directory `{}`", dir.display()))?;
+        // Keep the cache out of version control without touching the project's .gitignore
+        fs::write(dir.join(".gitignore"), "*\n")?;
+
+        let mut files = self.files.into_inner().unwrap();
+        files.retain(|_, entry| entry.stamp.is_some());
+
+        let data = CacheData {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            files,
+            history: self.history,
+        };
+
+        // Write to a temporary file first so an interrupted scan never leaves a truncated cache
+        let cache_file = dir.join(CACHE_FILE_NAME);
+        let temp_file = dir.join(format!("{}.tmp", CACHE_FILE_NAME));
+        fs::write(&temp_file, serde_json::to_string(&data)?)
+            .with_context(|| format!("could not write cache file `{}`", temp_file.display()))?;
+        fs::rename(&temp_file, &cache_file)
+            .with_context(|| format!("could not write cache file `{}`", cache_file.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScanSettings;
+    use tempfile::TempDir;
+
+    fn walk(path: &Path) -> FileTree {
+        FileTree::walk(path, &ScanSettings::default()).unwrap()
+    }
+
+    #[test]
+    fn test_file_summary() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("index.ts");
+        fs::write(
+            &file,
+            "import { a } from './a';\n// TODO: remove\n// todo\n// --feature-flag name: beta\n",
+        )
+        .unwrap();
+
+        let tree = walk(temp_dir.path());
+        let summary = ScanCache::in_memory(&tree).file_summary(&file);
+
+        assert_eq!(summary.lines, 4);
+        assert_eq!(summary.todos, 2);
+        assert_eq!(summary.imports.len(), 1);
+        assert_eq!(summary.imports[0].imported_path, "./a");
+        assert_eq!(summary.metadata.len(), 1);
+        assert_eq!(summary.metadata[0].metadata_key, "flag");
+        assert_eq!(summary.metadata[0].line_number, 4);
+    }
+
+    #[test]
+    fn test_summarize_all() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = (0..50)
+            .map(|i| temp_dir.path().join(format!("file{}.ts", i)))
+            .collect();
+        for (i, path) in paths.iter().enumerate() {
+            fs::write(path, "line\n".repeat(i)).unwrap();
+        }
+        let files: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+
+        let tree = walk(temp_dir.path());
+        let cache = ScanCache::in_memory(&tree);
+        let progress = ProgressBar::hidden();
+        cache.summarize_all(&files, &progress);
+
+        assert_eq!(progress.position(), 50);
+        assert_eq!(cache.files.lock().unwrap().len(), 50);
+        for (i, path) in files.iter().enumerate() {
+            assert_eq!(cache.file_summary(path).lines, i);
+        }
+    }
+
+    #[test]
+    fn test_saved_summaries_are_reused_until_the_file_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("index.ts");
+        fs::write(&file, "one\n").unwrap();
+
+        let tree = walk(temp_dir.path());
+        let cache = ScanCache::load(&tree);
+        assert_eq!(cache.file_summary(&file).lines, 1);
+        cache.save().unwrap();
+        assert!(
+            temp_dir
+                .path()
+                .join(CACHE_DIR_NAME)
+                .join(".gitignore")
+                .exists()
+        );
+
+        // Tamper with the saved entry to tell a cache hit from a fresh read
+        let cache_file = temp_dir.path().join(CACHE_DIR_NAME).join(CACHE_FILE_NAME);
+        let mut data: CacheData =
+            serde_json::from_str(&fs::read_to_string(&cache_file).unwrap()).unwrap();
+        data.files.get_mut("index.ts").unwrap().summary.lines = 42;
+        fs::write(&cache_file, serde_json::to_string(&data).unwrap()).unwrap();
+
+        assert_eq!(ScanCache::load(&tree).file_summary(&file).lines, 42);
+
+        fs::write(&file, "one\ntwo\n").unwrap();
+        assert_eq!(ScanCache::load(&tree).file_summary(&file).lines, 2);
+    }
+
+    #[test]
+    fn test_cache_of_another_version_is_discarded() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join(CACHE_DIR_NAME);
+        fs::create_dir_all(&dir).unwrap();
+
+        let data = CacheData {
+            version: "0.0.0".to_string(),
+            files: HashMap::new(),
+            history: Some(CommitHistory::default()),
+        };
+        fs::write(
+            dir.join(CACHE_FILE_NAME),
+            serde_json::to_string(&data).unwrap(),
+        )
+        .unwrap();
+
+        let tree = walk(temp_dir.path());
+        assert!(ScanCache::load(&tree).take_history().is_none());
+    }
+}
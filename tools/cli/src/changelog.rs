@@ -0,0 +1,372 @@
+//! Module for the `changelog` command
+//!
+//! The own commits of each feature, those only touching its nested features being left to
+//! them, are read as conventional commits (`type(scope)!: subject`) and grouped into breaking
+//! changes, features, fixes and performance improvements, other commit types being left out.
+//! A commit is breaking when its type ends with `!` or its body has a `BREAKING CHANGE:`
+//! footer, and is then only listed with the breaking changes.
+
+use anyhow::{Context, Result};
+use git2::Repository;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::file_scanner::extract_commit_type;
+use crate::models::{Change, Feature, flatten_features};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangelogEntry {
+    pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    pub subject: String,
+    pub author_name: String,
+    pub date: String,
+    /// Text of the `BREAKING CHANGE:` footer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breaking_note: Option<String>,
+}
+
+/// Changelog of a feature, newest commits first
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureChangelog {
+    pub name: String,
+    pub path: String,
+    pub owner: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub breaking_changes: Vec<ChangelogEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<ChangelogEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<ChangelogEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub performance: Vec<ChangelogEntry>,
+}
+
+impl FeatureChangelog {
+    fn is_empty(&self) -> bool {
+        self.breaking_changes.is_empty()
+            && self.features.is_empty()
+            && self.fixes.is_empty()
+            && self.performance.is_empty()
+    }
+}
+
+/// Hashes of the commits reachable from `to` (HEAD by default) but not from `from`, the
+/// previous release
+pub fn unreleased_commits(
+    base_path: &Path,
+    from: &str,
+    to: Option<&str>,
+) -> Result<HashSet<String>> {
+    let repo = Repository::discover(base_path).with_context(|| {
+        format!(
+            "failed to discover git repository at `{}`",
+            base_path.display()
+        )
+    })?;
+    let resolve = |revision: &str| {
+        repo.revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .map(|commit| commit.id())
+            .with_context(|| format!("could not resolve revision `{}`", revision))
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(resolve(to.unwrap_or("HEAD"))?)?;
+    revwalk.hide(resolve(from)?)?;
+
+    revwalk
+        .map(|oid| Ok(oid?.to_string()))
+        .collect::<Result<_>>()
+}
+
+/// Changelog of every feature with at least one listed commit, sorted by path
+///
+/// Only the commits of `unreleased` are listed when it is set.
+pub fn build_changelog(
+    features: &[Feature],
+    unreleased: Option<&HashSet<String>>,
+) -> Vec<FeatureChangelog> {
+    let mut changelogs = Vec::new();
+    for feature in flatten_features(features) {
+        let mut changelog = FeatureChangelog {
+            name: feature.name.clone(),
+            path: feature.path.clone(),
+            owner: feature.owner.clone(),
+            breaking_changes: Vec::new(),
+            features: Vec::new(),
+            fixes: Vec::new(),
+            performance: Vec::new(),
+        };
+
+        for change in feature.own_changes() {
+            if unreleased.is_some_and(|unreleased| !unreleased.contains(&change.hash)) {
+                continue;
+            }
+
+            let (commit_type, entry) = parse_change(change);
+            let section = if entry.breaking_note.is_some() || is_breaking(&change.title) {
+                &mut changelog.breaking_changes
+            } else {
+                match commit_type.as_str() {
+                    "feat" => &mut changelog.features,
+                    "fix" => &mut changelog.fixes,
+                    "perf" => &mut changelog.performance,
+                    _ => continue,
+                }
+            };
+            section.push(entry);
+        }
+
+        if !changelog.is_empty() {
+            changelogs.push(changelog);
+        }
+    }
+
+    changelogs.sort_by(|a, b| a.path.cmp(&b.path));
+    changelogs
+}
+
+fn parse_change(change: &Change) -> (String, ChangelogEntry) {
+    let commit_type = extract_commit_type(&change.title);
+
+    let (scope, subject) = match change.title.split_once(':') {
+        Some((prefix, subject)) if commit_type != "other" => {
+            let scope = prefix
+                .split_once('(')
+                .and_then(|(_, scope)| scope.split_once(')'))
+                .map(|(scope, _)| scope.trim().to_string())
+                .filter(|scope| !scope.is_empty());
+            (scope, subject.trim().to_string())
+        }
+        _ => (None, change.title.trim().to_string()),
+    };
+
+    let entry = ChangelogEntry {
+        hash: change.hash.clone(),
+        scope,
+        subject,
+        author_name: change.author_name.clone(),
+        date: change.date.clone(),
+        breaking_note: breaking_note(&change.description),
+    };
+
+    (commit_type, entry)
+}
+
+/// Whether the type of a conventional commit title ends with `!` (e.g. `feat(api)!: ...`)
+fn is_breaking(title: &str) -> bool {
+    extract_commit_type(title) != "other"
+        && title
+            .split_once(':')
+            .is_some_and(|(prefix, _)| prefix.trim_end().ends_with('!'))
+}
+
+/// The `BREAKING CHANGE:` footer of a commit body, up to the end of its paragraph
+fn breaking_note(description: &str) -> Option<String> {
+    let mut lines = description.lines();
+    let first_line = lines.find_map(|line| {
+        line.strip_prefix("BREAKING CHANGE:")
+            .or_else(|| line.strip_prefix("BREAKING-CHANGE:"))
+    })?;
+
+    let mut note = first_line.trim().to_string();
+    for line in lines.take_while(|line| !line.trim().is_empty()) {
+        note.push(' ');
+        note.push_str(line.trim());
+    }
+    Some(note)
+}
+
+/// One section per feature, `from` and `to` being the bounds shown in the title
+pub fn format_markdown(
+    changelogs: &[FeatureChangelog],
+    from: Option<&str>,
+    to: Option<&str>,
+) -> String {
+    let mut output = String::from("# Changelog\n");
+
+    match (from, to) {
+        (Some(from), Some(to)) => {
+            let _ = writeln!(output, "\nChanges from `{}` to `{}`.", from, to);
+        }
+        (Some(from), None) => {
+            let _ = writeln!(output, "\nChanges since `{}`.", from);
+        }
+        (None, Some(to)) => {
+            let _ = writeln!(output, "\nChanges up to `{}`.", to);
+        }
+        (None, None) => {}
+    }
+
+    if changelogs.is_empty() {
+        output.push_str("\nNo feature changes.\n");
+        return output;
+    }
+
+    for changelog in changelogs {
+        let _ = writeln!(output, "\n## {} (`{}`)", changelog.name, changelog.path);
+        if !changelog.owner.is_empty() {
+            let _ = writeln!(output, "\nOwner: {}", changelog.owner);
+        }
+
+        for (title, entries) in [
+            ("Breaking changes", &changelog.breaking_changes),
+            ("Features", &changelog.features),
+            ("Bug fixes", &changelog.fixes),
+            ("Performance", &changelog.performance),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+
+            let _ = writeln!(output, "\n### {}\n", title);
+            for entry in entries {
+                output.push_str("- ");
+                if let Some(scope) = &entry.scope {
+                    let _ = write!(output, "**{}:** ", scope);
+                }
+                let short_hash = entry.hash.get(..7).unwrap_or(&entry.hash);
+                let _ = writeln!(output, "{} ({})", entry.subject, short_hash);
+                if let Some(note) = &entry.breaking_note {
+                    let _ = writeln!(output, "  {}", note);
+                }
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_fixture::commit_file;
+    use tempfile::TempDir;
+
+    fn change(hash: &str, title: &str, description: &str) -> Change {
+        Change {
+            title: title.to_string(),
+            author_name: "Alice".to_string(),
+            author_email: "alice@example.com".to_string(),
+            description: description.to_string(),
+            date: "2025-01-01 00:00:00".to_string(),
+            hash: hash.to_string(),
+            linked_by_message: false,
+            nested_only: false,
+        }
+    }
+
+    fn feature(path: &str, changes: Vec<Change>) -> Feature {
+        Feature {
+            owner: "team-a".to_string(),
+            changes,
+            ..Feature::at(path)
+        }
+    }
+
+    #[test]
+    fn test_build_changelog() {
+        let features = vec![feature(
+            "features/auth",
+            vec![
+                change("a000000001", "feat(login): add SSO", ""),
+                change("a000000002", "fix!: reject expired tokens", ""),
+                change(
+                    "a000000003",
+                    "refactor(api): rename the session endpoint",
+                    "Details\n\nBREAKING CHANGE: clients must call\n/session instead of /login\n\nRefs #12",
+                ),
+                change("a000000004", "perf: cache the public keys", ""),
+                change("a000000005", "docs: explain SSO", ""),
+                change("a000000006", "feat: released earlier", ""),
+            ],
+        )];
+        let unreleased = (1..=5).map(|index| format!("a00000000{}", index)).collect();
+
+        let changelogs = build_changelog(&features, Some(&unreleased));
+
+        assert_eq!(changelogs.len(), 1);
+        let changelog = &changelogs[0];
+        let hashes = |entries: &[ChangelogEntry]| -> Vec<String> {
+            entries.iter().map(|entry| entry.hash.clone()).collect()
+        };
+        assert_eq!(
+            hashes(&changelog.breaking_changes),
+            vec!["a000000002", "a000000003"]
+        );
+        assert_eq!(hashes(&changelog.features), vec!["a000000001"]);
+        assert_eq!(hashes(&changelog.fixes), Vec::<String>::new());
+        assert_eq!(hashes(&changelog.performance), vec!["a000000004"]);
+
+        assert_eq!(changelog.features[0].scope.as_deref(), Some("login"));
+        assert_eq!(changelog.features[0].subject, "add SSO");
+        assert_eq!(
+            changelog.breaking_changes[1].breaking_note.as_deref(),
+            Some("clients must call /session instead of /login")
+        );
+
+        let markdown = format_markdown(&changelogs, Some("v1.0.0"), Some("v1.1.0"));
+        assert!(markdown.contains("Changes from `v1.0.0` to `v1.1.0`."));
+        assert!(markdown.contains("## auth (`features/auth`)\n"));
+        assert!(markdown.contains("### Features\n\n- **login:** add SSO (a000000)\n"));
+        assert!(markdown.contains(
+            "- **api:** rename the session endpoint (a000000)\n  clients must call /session instead of /login\n"
+        ));
+    }
+
+    #[test]
+    fn test_is_breaking() {
+        assert!(is_breaking("feat!: drop the v1 API"));
+        assert!(is_breaking("fix(auth)!: reject old tokens"));
+        assert!(!is_breaking("feat: add SSO"));
+        // Only conventional commit types can be marked as breaking
+        assert!(!is_breaking("Wow!: note"));
+    }
+
+    #[test]
+    fn test_unreleased_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let first = commit_file(&repo, "a.ts", "a", 1_000);
+        repo.tag_lightweight("v1", &repo.find_object(first, None).unwrap(), false)
+            .unwrap();
+        let second = commit_file(&repo, "a.ts", "a2", 2_000);
+        let third = commit_file(&repo, "a.ts", "a3", 3_000);
+
+        let unreleased = unreleased_commits(temp_dir.path(), "v1", None).unwrap();
+        assert_eq!(
+            unreleased,
+            HashSet::from([second.to_string(), third.to_string()])
+        );
+
+        let second = second.to_string();
+        let unreleased = unreleased_commits(temp_dir.path(), "v1", Some(&second)).unwrap();
+        assert_eq!(unreleased, HashSet::from([second]));
+        assert!(unreleased_commits(temp_dir.path(), "v2", None).is_err());
+    }
+
+    #[test]
+    fn test_features_without_listed_commits_are_left_out() {
+        let features = vec![feature(
+            "features/docs",
+            vec![
+                change("b000000001", "docs: typo", ""),
+                change("b000000002", "wip", ""),
+                // Listed in the changelog of the nested feature only
+                Change {
+                    nested_only: true,
+                    ..change("b000000003", "feat: add the API guide", "")
+                },
+            ],
+        )];
+
+        let changelogs = build_changelog(&features, None);
+
+        assert!(changelogs.is_empty());
+        assert!(format_markdown(&changelogs, None, None).contains("No feature changes."));
+    }
+}
@@ -0,0 +1,268 @@
+//! Module for the output formats of the `check` command
+//!
+//! Diagnostics are printed as text on stderr by default, or as JSON or SARIF 2.1.0 on stdout
+//! so code scanning tools can annotate pull requests with them. SARIF locations are relative
+//! to the root of the git repository containing the scanned folder, when there is one.
+
+use anyhow::Result;
+use git2::Repository;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::checker::{Diagnostic, RULES, Severity, count_by_severity};
+use crate::config::CheckSettings;
+use crate::feature_detection::find_readme_file;
+use crate::features_toml_parser::find_features_toml;
+use crate::walker::Source;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Output format of the check results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
+    Sarif,
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            _ => anyhow::bail!("invalid format `{}`, expected text, json or sarif", s),
+        }
+    }
+}
+
+/// One line per diagnostic: `severity[rule-id] location: message`
+pub fn format_text(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let location = match (&diagnostic.file, diagnostic.line) {
+                (Some(file), Some(line)) => format!("{}:{}", file, line),
+                (Some(file), None) => file.clone(),
+                _ => diagnostic.feature_path.clone(),
+            };
+            format!(
+                "{}[{}] {}: {}\n",
+                diagnostic.severity, diagnostic.rule_id, location, diagnostic.message
+            )
+        })
+        .collect()
+}
+
+/// Diagnostics and their count by severity
+pub fn to_json(diagnostics: &[Diagnostic]) -> Value {
+    json!({
+        "diagnostics": diagnostics,
+        "summary": {
+            "errors": count_by_severity(diagnostics, Severity::Error),
+            "warnings": count_by_severity(diagnostics, Severity::Warning),
+        },
+    })
+}
+
+/// A SARIF 2.1.0 log with a single run
+///
+/// Diagnostics without a file are located at the FEATURES.toml or README of their feature,
+/// or at the feature folder itself.
+pub fn to_sarif(diagnostics: &[Diagnostic], base_path: &Path, settings: &CheckSettings) -> Value {
+    let uri_base = repository_relative_path(base_path);
+
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+                "defaultConfiguration": { "level": sarif_level(rule.severity(settings)) },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let rule_index = RULES.iter().position(|rule| rule.id == diagnostic.rule_id);
+
+            let file = match &diagnostic.file {
+                Some(file) => file.clone(),
+                None => feature_location(base_path, &diagnostic.feature_path),
+            };
+            let mut physical_location = json!({
+                "artifactLocation": { "uri": to_uri(&uri_base.join(file)) },
+            });
+            if let Some(line) = diagnostic.line {
+                physical_location["region"] = json!({ "startLine": line });
+            }
+
+            json!({
+                "ruleId": diagnostic.rule_id,
+                "ruleIndex": rule_index,
+                "level": sarif_level(diagnostic.severity),
+                "message": { "text": diagnostic.message },
+                "locations": [{ "physicalLocation": physical_location }],
+                "properties": { "featurePath": diagnostic.feature_path },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "features",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Off => "none",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+/// File describing a feature, relative to the scanned folder
+fn feature_location(base_path: &Path, feature_path: &str) -> String {
+    let feature_dir = base_path.join(feature_path);
+
+    // Diagnostics point at the working directory, even for a scan of another revision
+    let source = Source::WorkingDirectory;
+    find_features_toml(&feature_dir, &source)
+        .or_else(|| find_readme_file(&feature_dir, &source))
+        .and_then(|file| {
+            file.file_name()
+                .map(|name| Path::new(feature_path).join(name))
+        })
+        .map(|file| file.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("{}/", feature_path))
+}
+
+/// Path of the scanned folder relative to the root of its git repository (empty outside one)
+fn repository_relative_path(base_path: &Path) -> PathBuf {
+    let Ok(repo) = Repository::discover(base_path) else {
+        return PathBuf::new();
+    };
+
+    let canonical_base = std::fs::canonicalize(base_path).ok();
+    let canonical_workdir = repo
+        .workdir()
+        .and_then(|dir| std::fs::canonicalize(dir).ok());
+
+    match (canonical_base, canonical_workdir) {
+        (Some(base), Some(workdir)) => base
+            .strip_prefix(&workdir)
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+        _ => PathBuf::new(),
+    }
+}
+
+fn to_uri(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic {
+                rule_id: "missing-owner",
+                severity: Severity::Warning,
+                message: "Feature 'a' has no owner".to_string(),
+                feature_path: "features/a".to_string(),
+                file: None,
+                line: None,
+            },
+            Diagnostic {
+                rule_id: "sibling-dependency",
+                severity: Severity::Error,
+                message: "Feature 'a' imports its sibling feature 'features/b'".to_string(),
+                feature_path: "features/a".to_string(),
+                file: Some("features/a/index.ts".to_string()),
+                line: Some(4),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            format_text(&diagnostics()),
+            "warning[missing-owner] features/a: Feature 'a' has no owner\n\
+             error[sibling-dependency] features/a/index.ts:4: Feature 'a' imports its sibling feature 'features/b'\n"
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let report = to_json(&diagnostics());
+
+        assert_eq!(report["summary"]["errors"], 1);
+        assert_eq!(report["summary"]["warnings"], 1);
+        assert_eq!(report["diagnostics"][1]["rule_id"], "sibling-dependency");
+        assert_eq!(report["diagnostics"][1]["line"], 4);
+        assert_eq!(report["diagnostics"][0]["file"], Value::Null);
+    }
+
+    #[test]
+    fn test_to_sarif() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("features/a")).unwrap();
+        std::fs::write(temp_dir.path().join("features/a/README.md"), "# A\n").unwrap();
+
+        let sarif = to_sarif(&diagnostics(), temp_dir.path(), &CheckSettings::default());
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            RULES.len()
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "features/a/README.md"
+        );
+        assert_eq!(results[1]["ruleId"], "sibling-dependency");
+        assert_eq!(
+            RULES[results[1]["ruleIndex"].as_u64().unwrap() as usize].id,
+            "sibling-dependency"
+        );
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            4
+        );
+    }
+
+    #[test]
+    fn test_report_format_from_str() {
+        assert_eq!(
+            "sarif".parse::<ReportFormat>().unwrap(),
+            ReportFormat::Sarif
+        );
+        assert!("xml".parse::<ReportFormat>().is_err());
+    }
+}
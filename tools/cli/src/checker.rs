@@ -1,52 +1,887 @@
+//! Module for the architecture checks run by the `check` command
+//!
+//! Every check is a [`Rule`] of the [`RULES`] registry, identified by a stable id. Each rule
+//! has a default [`Severity`] that the `[check.rules]` table of the configuration file (or
+//! `--rule <id>=<severity>`) can change, `off` disabling the rule. Only `error` diagnostics
+//! make the check fail, so CI can be gated on a subset of the rules.
+//!
+//! Dependency policies are rules as well: `sibling-dependency` and `parent-dependency` are
+//! off by default and forbid a kind of import once enabled, while `forbidden-dependency`
+//! enforces the `allowed_dependencies` and `forbidden_dependencies` lists a feature declares
+//! in its FEATURES.toml or README front matter.
+
 use anyhow::Result;
-use std::collections::HashMap;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::check_report::{ReportFormat, format_text, to_json, to_sarif};
+
+use crate::config::CheckSettings;
+use crate::models::{Dependency, DependencyType, Feature, flatten_features};
+
+/// How a rule violation is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Severity::Off => "off",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "off" => Ok(Severity::Off),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => anyhow::bail!("invalid severity `{}`, expected off, warning or error", s),
+        }
+    }
+}
 
-use crate::models::Feature;
+/// A problem found by a rule
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub rule_id: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Path of the feature the problem belongs to
+    pub feature_path: String,
+    /// File the problem was found in, relative to the scanned folder
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
 
-pub fn run_checks(features: &[Feature]) -> Result<()> {
-    let mut error_count = 0;
+/// A problem found by a rule, before its severity is resolved
+struct Violation {
+    message: String,
+    feature_path: String,
+    file: Option<String>,
+    line: Option<usize>,
+}
 
-    error_count += check_duplicate_names(features);
+impl Violation {
+    fn new(feature: &Feature, message: String) -> Self {
+        Self {
+            message,
+            feature_path: feature.path.clone(),
+            file: None,
+            line: None,
+        }
+    }
+
+    /// A violation located at the import statement of a dependency
+    fn at_dependency(feature: &Feature, dependency: &Dependency, message: String) -> Self {
+        Self {
+            message: format!("{}: {}", message, dependency.content),
+            feature_path: feature.path.clone(),
+            file: Some(dependency.source_filename.clone()),
+            line: Some(dependency.line),
+        }
+    }
+}
+
+/// A check of the registry
+pub struct Rule {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub default_severity: Severity,
+    check: fn(&[&Feature], &CheckSettings) -> Vec<Violation>,
+}
+
+/// Every available rule
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "duplicate-name",
+        description: "Two features have the same name",
+        default_severity: Severity::Error,
+        check: check_duplicate_names,
+    },
+    Rule {
+        id: "missing-owner",
+        description: "Feature without an owner, even inherited",
+        default_severity: Severity::Warning,
+        check: check_missing_owner,
+    },
+    Rule {
+        id: "missing-description",
+        description: "Feature without a README or FEATURES.toml description",
+        default_severity: Severity::Warning,
+        check: check_missing_description,
+    },
+    Rule {
+        id: "unknown-owner",
+        description: "Owner not listed in `allowed_owners` (only when the list is set)",
+        default_severity: Severity::Error,
+        check: check_unknown_owner,
+    },
+    Rule {
+        id: "invalid-status",
+        description: "`status` metadata not listed in `allowed_statuses` (only when the list is set)",
+        default_severity: Severity::Error,
+        check: check_invalid_status,
+    },
+    Rule {
+        id: "deprecated-dependency",
+        description: "Feature importing a feature whose status is `deprecated`",
+        default_severity: Severity::Warning,
+        check: check_deprecated_dependencies,
+    },
+    Rule {
+        id: "sibling-dependency",
+        description: "Feature importing a feature that is neither its parent nor its child",
+        default_severity: Severity::Off,
+        check: check_sibling_dependencies,
+    },
+    Rule {
+        id: "parent-dependency",
+        description: "Nested feature importing one of its parent features",
+        default_severity: Severity::Off,
+        check: check_parent_dependencies,
+    },
+    Rule {
+        id: "forbidden-dependency",
+        description: "Import breaking the `allowed_dependencies` or `forbidden_dependencies` of a feature",
+        default_severity: Severity::Error,
+        check: check_dependency_lists,
+    },
+    Rule {
+        id: "empty-feature",
+        description: "Feature folder without files nor nested features",
+        default_severity: Severity::Warning,
+        check: check_empty_features,
+    },
+    Rule {
+        id: "decision-missing-title",
+        description: "Decision record not starting with a `# Title` heading",
+        default_severity: Severity::Warning,
+        check: check_decision_titles,
+    },
+];
+
+impl Rule {
+    /// Severity of the rule once the configured overrides are applied
+    pub fn severity(&self, settings: &CheckSettings) -> Severity {
+        settings
+            .rules
+            .get(self.id)
+            .copied()
+            .unwrap_or(self.default_severity)
+    }
+}
+
+/// Finds a rule of the registry by id
+pub fn find_rule(id: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.id == id)
+}
+
+/// Runs every enabled rule and returns the diagnostics sorted by feature path
+pub fn collect_diagnostics(
+    features: &[Feature],
+    settings: &CheckSettings,
+) -> Result<Vec<Diagnostic>> {
+    if let Some(unknown) = settings.rules.keys().find(|id| find_rule(id).is_none()) {
+        anyhow::bail!("unknown rule `{}` in check settings", unknown);
+    }
+
+    let all_features = flatten_features(features);
+
+    let mut diagnostics = Vec::new();
+
+    for rule in RULES {
+        let severity = rule.severity(settings);
+        if severity == Severity::Off {
+            continue;
+        }
+
+        for violation in (rule.check)(&all_features, settings) {
+            diagnostics.push(Diagnostic {
+                rule_id: rule.id,
+                severity,
+                message: violation.message,
+                feature_path: violation.feature_path,
+                file: violation.file,
+                line: violation.line,
+            });
+        }
+    }
+
+    diagnostics.sort_by(|a, b| {
+        (&a.feature_path, &a.file, a.line).cmp(&(&b.feature_path, &b.file, b.line))
+    });
+
+    Ok(diagnostics)
+}
+
+/// Runs the checks and reports the diagnostics in the given format
+///
+/// Text goes to stderr, JSON and SARIF to stdout. Fails when any diagnostic is an error.
+pub fn run_checks(
+    features: &[Feature],
+    base_path: &Path,
+    settings: &CheckSettings,
+    format: ReportFormat,
+) -> Result<()> {
+    let diagnostics = collect_diagnostics(features, settings)?;
+
+    match format {
+        ReportFormat::Text => eprint!("{}", format_text(&diagnostics)),
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&to_json(&diagnostics))?),
+        ReportFormat::Sarif => println!(
+            "{}",
+            serde_json::to_string_pretty(&to_sarif(&diagnostics, base_path, settings))?
+        ),
+    }
+
+    let error_count = count_by_severity(&diagnostics, Severity::Error);
+    let warning_count = count_by_severity(&diagnostics, Severity::Warning);
 
     if error_count > 0 {
-        anyhow::bail!("Check failed: {} error(s) found", error_count);
+        anyhow::bail!(
+            "Check failed: {} error(s) and {} warning(s) found",
+            error_count,
+            warning_count
+        );
     }
 
-    eprintln!("All checks passed successfully.");
+    if warning_count > 0 {
+        eprintln!("All checks passed with {} warning(s).", warning_count);
+    } else {
+        eprintln!("All checks passed successfully.");
+    }
     Ok(())
 }
 
-fn collect_features(features: &[Feature], name_to_paths: &mut HashMap<String, Vec<String>>) {
+pub fn count_by_severity(diagnostics: &[Diagnostic], severity: Severity) -> usize {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == severity)
+        .count()
+}
+
+fn status_of(feature: &Feature) -> Option<&serde_json::Value> {
+    feature.meta.get("status")
+}
+
+fn check_duplicate_names(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    let mut name_to_features: BTreeMap<&str, Vec<&Feature>> = BTreeMap::new();
+
     for feature in features {
-        name_to_paths
-            .entry(feature.name.clone())
+        name_to_features
+            .entry(feature.name.as_str())
             .or_default()
-            .push(feature.path.clone());
+            .push(feature);
+    }
+
+    name_to_features
+        .into_iter()
+        .filter(|(_, features)| features.len() > 1)
+        .map(|(name, features)| {
+            let paths: Vec<&str> = features.iter().map(|f| f.path.as_str()).collect();
+            Violation::new(
+                features[0],
+                format!(
+                    "Duplicate feature name '{}' found in {} locations: {}",
+                    name,
+                    paths.len(),
+                    paths.join(", ")
+                ),
+            )
+        })
+        .collect()
+}
+
+fn check_missing_owner(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    features
+        .iter()
+        .filter(|feature| feature.owner.trim().is_empty())
+        .map(|feature| Violation::new(feature, format!("Feature '{}' has no owner", feature.name)))
+        .collect()
+}
+
+fn check_missing_description(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    features
+        .iter()
+        .filter(|feature| feature.description.trim().is_empty())
+        .map(|feature| {
+            Violation::new(
+                feature,
+                format!("Feature '{}' has no README or description", feature.name),
+            )
+        })
+        .collect()
+}
+
+fn check_unknown_owner(features: &[&Feature], settings: &CheckSettings) -> Vec<Violation> {
+    if settings.allowed_owners.is_empty() {
+        return Vec::new();
+    }
+
+    features
+        .iter()
+        // Inherited owners are reported on the feature declaring them
+        .filter(|feature| !feature.is_owner_inherited && !feature.owner.is_empty())
+        .filter(|feature| !settings.allowed_owners.contains(&feature.owner))
+        .map(|feature| {
+            Violation::new(
+                feature,
+                format!(
+                    "Owner '{}' of feature '{}' is not an allowed owner",
+                    feature.owner, feature.name
+                ),
+            )
+        })
+        .collect()
+}
+
+fn check_invalid_status(features: &[&Feature], settings: &CheckSettings) -> Vec<Violation> {
+    if settings.allowed_statuses.is_empty() {
+        return Vec::new();
+    }
+
+    let mut violations = Vec::new();
+
+    for feature in features {
+        let Some(status) = status_of(feature) else {
+            continue;
+        };
+
+        match status.as_str() {
+            Some(status) if settings.allowed_statuses.iter().any(|s| s == status) => {}
+            Some(status) => violations.push(Violation::new(
+                feature,
+                format!(
+                    "Feature '{}' has an invalid status '{}', expected one of: {}",
+                    feature.name,
+                    status,
+                    settings.allowed_statuses.join(", ")
+                ),
+            )),
+            None => violations.push(Violation::new(
+                feature,
+                format!(
+                    "Feature '{}' has a status that is not a string",
+                    feature.name
+                ),
+            )),
+        }
+    }
+
+    violations
+}
+
+fn check_deprecated_dependencies(
+    features: &[&Feature],
+    _settings: &CheckSettings,
+) -> Vec<Violation> {
+    let is_deprecated =
+        |feature: &Feature| status_of(feature).and_then(|s| s.as_str()) == Some("deprecated");
+
+    let deprecated: HashSet<&str> = features
+        .iter()
+        .filter(|feature| is_deprecated(feature))
+        .map(|feature| feature.path.as_str())
+        .collect();
+
+    let mut violations = Vec::new();
+
+    for feature in features {
+        // Deprecated features may keep depending on each other
+        if is_deprecated(feature) {
+            continue;
+        }
+
+        for dependency in &feature.dependencies {
+            if deprecated.contains(dependency.feature_path.as_str()) {
+                violations.push(Violation::at_dependency(
+                    feature,
+                    dependency,
+                    format!(
+                        "Feature '{}' imports deprecated feature '{}'",
+                        feature.name, dependency.feature_path
+                    ),
+                ));
+            }
+        }
+    }
+
+    violations
+}
+
+fn check_dependency_type(
+    features: &[&Feature],
+    dependency_type: DependencyType,
+    relation: &str,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
 
-        collect_features(&feature.features, name_to_paths);
+    for feature in features {
+        for dependency in &feature.dependencies {
+            if dependency.dependency_type == dependency_type {
+                violations.push(Violation::at_dependency(
+                    feature,
+                    dependency,
+                    format!(
+                        "Feature '{}' imports its {} feature '{}'",
+                        feature.name, relation, dependency.feature_path
+                    ),
+                ));
+            }
+        }
     }
+
+    violations
 }
 
-fn check_duplicate_names(features: &[Feature]) -> usize {
-    let mut name_to_paths: HashMap<String, Vec<String>> = HashMap::new();
+fn check_sibling_dependencies(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    check_dependency_type(features, DependencyType::Sibling, "sibling")
+}
 
-    collect_features(features, &mut name_to_paths);
+fn check_parent_dependencies(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    check_dependency_type(features, DependencyType::Parent, "parent")
+}
 
-    let mut error_count = 0;
+/// A list of feature names, paths or path globs declared in the metadata of a feature
+struct DependencyList {
+    entries: Vec<String>,
+    /// Entries compiled as globs, `*` not crossing folders
+    globs: GlobSet,
+}
+
+impl DependencyList {
+    fn read(feature: &Feature, key: &str) -> Option<Self> {
+        let value = feature.meta.get(key)?;
+
+        let entries: Vec<String> = match value {
+            serde_json::Value::String(entry) => vec![entry.clone()],
+            serde_json::Value::Array(entries) => entries
+                .iter()
+                .filter_map(|entry| entry.as_str().map(String::from))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let entries: Vec<String> = entries
+            .iter()
+            .map(|entry| entry.trim_end_matches('/').to_string())
+            .collect();
 
-    for (name, paths) in name_to_paths.iter() {
-        if paths.len() > 1 {
-            error_count += 1;
-            eprintln!(
-                "Error: Duplicate feature name '{}' found in {} locations:",
-                name,
-                paths.len()
-            );
-            for path in paths {
-                eprintln!("  - {}", path);
+        // Entries that are not valid globs still match by name or path
+        let mut builder = GlobSetBuilder::new();
+        for entry in &entries {
+            if let Ok(glob) = GlobBuilder::new(entry).literal_separator(true).build() {
+                builder.add(glob);
             }
         }
+        let globs = builder.build().unwrap_or_default();
+
+        Some(Self { entries, globs })
+    }
+
+    /// Check if an entry designates a feature, by name, path or glob on the path
+    fn matches(&self, feature_path: &str, feature_name: Option<&str>) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry == feature_path || Some(entry.as_str()) == feature_name)
+            || self.globs.is_match(feature_path)
     }
+}
+
+fn check_dependency_lists(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    let names: HashMap<&str, &str> = features
+        .iter()
+        .map(|feature| (feature.path.as_str(), feature.name.as_str()))
+        .collect();
 
-    error_count
+    let mut violations = Vec::new();
+
+    for feature in features {
+        let allowed = DependencyList::read(feature, "allowed_dependencies");
+        let forbidden = DependencyList::read(feature, "forbidden_dependencies");
+        if allowed.is_none() && forbidden.is_none() {
+            continue;
+        }
+
+        for dependency in &feature.dependencies {
+            let target_path = dependency.feature_path.as_str();
+            let target_name = names.get(target_path).copied();
+
+            if let Some(forbidden) = &forbidden
+                && forbidden.matches(target_path, target_name)
+            {
+                violations.push(Violation::at_dependency(
+                    feature,
+                    dependency,
+                    format!(
+                        "Feature '{}' must not import '{}' (forbidden_dependencies)",
+                        feature.name, target_path
+                    ),
+                ));
+            } else if let Some(allowed) = &allowed
+                && !allowed.matches(target_path, target_name)
+            {
+                violations.push(Violation::at_dependency(
+                    feature,
+                    dependency,
+                    format!(
+                        "Feature '{}' may only import its allowed_dependencies, not '{}'",
+                        feature.name, target_path
+                    ),
+                ));
+            }
+        }
+    }
+
+    violations
+}
+
+fn check_empty_features(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    features
+        .iter()
+        .filter(|feature| {
+            feature.features.is_empty()
+                && feature.stats.as_ref().and_then(|stats| stats.files_count) == Some(0)
+        })
+        .map(|feature| Violation::new(feature, format!("Feature '{}' is empty", feature.name)))
+        .collect()
+}
+
+fn check_decision_titles(features: &[&Feature], _settings: &CheckSettings) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    for feature in features {
+        for (index, decision) in feature.decisions.iter().enumerate() {
+            if !has_title(decision) {
+                violations.push(Violation::new(
+                    feature,
+                    format!(
+                        "Decision #{} of feature '{}' does not start with a `# Title` heading",
+                        index + 1,
+                        feature.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    violations
+}
+
+/// Check if a markdown document starts with a level 1 heading, after its front matter
+fn has_title(content: &str) -> bool {
+    let body = content
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.find("\n---\n").map(|end| &rest[end + 5..]))
+        .unwrap_or(content);
+
+    body.lines()
+        .find(|line| !line.trim().is_empty())
+        .is_some_and(|line| line.starts_with("# ") && line.len() > 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Dependency, DependencyType, Stats};
+
+    fn feature(name: &str, path: &str) -> Feature {
+        Feature {
+            name: name.to_string(),
+            description: "A feature".to_string(),
+            owner: "team-a".to_string(),
+            stats: Some(Stats {
+                files_count: Some(1),
+                lines_count: Some(1),
+                todos_count: Some(0),
+                ..Stats::default()
+            }),
+            ..Feature::at(path)
+        }
+    }
+
+    fn rule_ids(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.rule_id).collect()
+    }
+
+    #[test]
+    fn test_clean_features_have_no_diagnostics() {
+        let features = vec![feature("a", "features/a"), feature("b", "features/b")];
+        let diagnostics = collect_diagnostics(&features, &CheckSettings::default()).unwrap();
+
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_each_rule_reports_its_violation() {
+        let mut duplicate = feature("a", "features/a");
+        duplicate
+            .features
+            .push(feature("a", "features/a/features/a"));
+
+        let mut no_owner = feature("no-owner", "features/no-owner");
+        no_owner.owner = String::new();
+
+        let mut no_description = feature("no-description", "features/no-description");
+        no_description.description = String::new();
+
+        let mut bad_status = feature("bad-status", "features/bad-status");
+        bad_status
+            .meta
+            .insert("status".to_string(), serde_json::json!("wip"));
+
+        let mut deprecated = feature("old", "features/old");
+        deprecated
+            .meta
+            .insert("status".to_string(), serde_json::json!("deprecated"));
+
+        let mut importer = feature("importer", "features/importer");
+        importer.dependencies.push(Dependency {
+            source_filename: "features/importer/index.ts".to_string(),
+            target_filename: "features/old/index.ts".to_string(),
+            line: 3,
+            content: "import { old } from '../old'".to_string(),
+            feature_path: "features/old".to_string(),
+            dependency_type: DependencyType::Sibling,
+        });
+
+        let mut empty = feature("empty", "features/empty");
+        empty.stats.as_mut().unwrap().files_count = Some(0);
+
+        let mut untitled = feature("untitled", "features/untitled");
+        untitled.decisions = vec![
+            "# Use Postgres\n\nBecause.".to_string(),
+            "Some decision without title".to_string(),
+        ];
+
+        let features = vec![
+            duplicate,
+            no_owner,
+            no_description,
+            bad_status,
+            deprecated,
+            importer,
+            empty,
+            untitled,
+        ];
+        let settings = CheckSettings {
+            allowed_statuses: vec!["active".to_string(), "deprecated".to_string()],
+            ..CheckSettings::default()
+        };
+        let diagnostics = collect_diagnostics(&features, &settings).unwrap();
+
+        assert_eq!(
+            rule_ids(&diagnostics),
+            vec![
+                "duplicate-name",
+                "invalid-status",
+                "empty-feature",
+                "deprecated-dependency",
+                "missing-description",
+                "missing-owner",
+                "decision-missing-title",
+            ]
+        );
+
+        let deprecated_import = &diagnostics[3];
+        assert_eq!(
+            deprecated_import.file.as_deref(),
+            Some("features/importer/index.ts")
+        );
+        assert_eq!(deprecated_import.line, Some(3));
+
+        // Any status is accepted until the allowed statuses are listed
+        let diagnostics = collect_diagnostics(&features, &CheckSettings::default()).unwrap();
+        assert!(!rule_ids(&diagnostics).contains(&"invalid-status"));
+    }
+
+    #[test]
+    fn test_severity_overrides_and_allowed_owners() {
+        let mut no_owner = feature("no-owner", "features/no-owner");
+        no_owner.owner = String::new();
+        let features = vec![feature("a", "features/a"), no_owner];
+
+        let settings = CheckSettings {
+            allowed_owners: vec!["team-b".to_string()],
+            rules: BTreeMap::from([
+                ("missing-owner".to_string(), Severity::Off),
+                ("unknown-owner".to_string(), Severity::Warning),
+            ]),
+            ..CheckSettings::default()
+        };
+
+        let diagnostics = collect_diagnostics(&features, &settings).unwrap();
+
+        assert_eq!(rule_ids(&diagnostics), vec!["unknown-owner"]);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(run_checks(&features, Path::new("."), &settings, ReportFormat::Text).is_ok());
+    }
+
+    fn dependency(source: &str, target: &str, dependency_type: DependencyType) -> Dependency {
+        Dependency {
+            source_filename: format!("{}/index.ts", source),
+            target_filename: format!("{}/index.ts", target),
+            line: 1,
+            content: format!("import '{}'", target),
+            feature_path: target.to_string(),
+            dependency_type,
+        }
+    }
+
+    #[test]
+    fn test_dependency_type_policies() {
+        let mut child = feature("child", "features/a/features/child");
+        child.dependencies = vec![
+            dependency(
+                "features/a/features/child",
+                "features/a",
+                DependencyType::Parent,
+            ),
+            dependency(
+                "features/a/features/child",
+                "features/b",
+                DependencyType::Sibling,
+            ),
+        ];
+        let mut parent = feature("a", "features/a");
+        parent.features.push(child);
+        let features = vec![parent, feature("b", "features/b")];
+
+        // Both policies are opt-in
+        let diagnostics = collect_diagnostics(&features, &CheckSettings::default()).unwrap();
+        assert!(diagnostics.is_empty());
+
+        let settings = CheckSettings {
+            rules: BTreeMap::from([
+                ("sibling-dependency".to_string(), Severity::Error),
+                ("parent-dependency".to_string(), Severity::Warning),
+            ]),
+            ..CheckSettings::default()
+        };
+        let diagnostics = collect_diagnostics(&features, &settings).unwrap();
+
+        assert_eq!(
+            rule_ids(&diagnostics),
+            vec!["sibling-dependency", "parent-dependency"]
+        );
+        assert_eq!(
+            diagnostics[0].file.as_deref(),
+            Some("features/a/features/child/index.ts")
+        );
+    }
+
+    #[test]
+    fn test_allowed_and_forbidden_dependency_lists() {
+        let mut restricted = feature("restricted", "features/restricted");
+        restricted.meta.insert(
+            "allowed_dependencies".to_string(),
+            serde_json::json!(["shared", "features/ui/*"]),
+        );
+        restricted.dependencies = vec![
+            dependency(
+                "features/restricted",
+                "features/shared",
+                DependencyType::Sibling,
+            ),
+            dependency(
+                "features/restricted",
+                "features/ui/button",
+                DependencyType::Sibling,
+            ),
+            dependency(
+                "features/restricted",
+                "features/billing",
+                DependencyType::Sibling,
+            ),
+        ];
+
+        let mut isolated = feature("isolated", "features/isolated");
+        isolated.meta.insert(
+            "forbidden_dependencies".to_string(),
+            serde_json::json!("features/billing"),
+        );
+        isolated.dependencies = vec![
+            dependency(
+                "features/isolated",
+                "features/shared",
+                DependencyType::Sibling,
+            ),
+            dependency(
+                "features/isolated",
+                "features/billing",
+                DependencyType::Sibling,
+            ),
+        ];
+
+        let features = vec![
+            restricted,
+            isolated,
+            feature("shared", "features/shared"),
+            feature("billing", "features/billing"),
+        ];
+        let diagnostics = collect_diagnostics(&features, &CheckSettings::default()).unwrap();
+
+        let targets: Vec<(&str, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.feature_path.as_str(), d.message.as_str()))
+            .collect();
+        assert_eq!(diagnostics.len(), 2, "{:?}", targets);
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.rule_id == "forbidden-dependency")
+        );
+        assert_eq!(diagnostics[0].feature_path, "features/isolated");
+        assert_eq!(diagnostics[1].feature_path, "features/restricted");
+        assert!(diagnostics[1].message.contains("features/billing"));
+    }
+
+    #[test]
+    fn test_dependency_list_globs_do_not_cross_folders() {
+        let mut feature = feature("isolated", "features/isolated");
+        feature.meta.insert(
+            "forbidden_dependencies".to_string(),
+            serde_json::json!(["features/*", "[invalid"]),
+        );
+        let forbidden = DependencyList::read(&feature, "forbidden_dependencies").unwrap();
+
+        assert!(forbidden.matches("features/a", None));
+        assert!(!forbidden.matches("features/a/features/b", None));
+        assert!(forbidden.matches("features/c", Some("[invalid")));
+    }
+
+    #[test]
+    fn test_unknown_rule_is_rejected() {
+        let mut settings = CheckSettings::default();
+        settings
+            .rules
+            .insert("no-such-rule".to_string(), Severity::Error);
+
+        assert!(collect_diagnostics(&[], &settings).is_err());
+    }
+
+    #[test]
+    fn test_has_title() {
+        assert!(has_title("# Title\n\nBody"));
+        assert!(has_title("\n\n# Title"));
+        assert!(has_title("---\nstatus: accepted\n---\n# Title\n"));
+        assert!(!has_title("## Subtitle"));
+        assert!(!has_title("Body"));
+        assert!(!has_title(""));
+    }
 }
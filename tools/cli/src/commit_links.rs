@@ -0,0 +1,227 @@
+//! Module linking commits to features by their message
+//!
+//! Commits are attached to features by the files they touch. A commit can also name the
+//! features it belongs to, with a `Feature: <name>` trailer in the last paragraph of its
+//! message or with the scope of a conventional commit title (`feat(checkout): ...`), so
+//! cross-cutting commits (configuration, migrations in shared folders) show up in the history
+//! of the right feature. Names are compared case-insensitively, spaces and underscores
+//! matching dashes.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::file_scanner::extract_commit_type;
+use crate::git_helper::CommitHistory;
+use crate::models::Change;
+
+/// Commits of a history naming features in their message, keyed by normalized feature name
+#[derive(Debug, Default)]
+pub struct CommitLinks {
+    changes: HashMap<String, Vec<Change>>,
+}
+
+impl CommitLinks {
+    pub fn from_history(history: &CommitHistory) -> Self {
+        let mut changes: HashMap<String, Vec<Change>> = HashMap::new();
+
+        for commit in &history.commits {
+            for name in linked_feature_names(&commit.change) {
+                let change = Change {
+                    linked_by_message: true,
+                    ..commit.change.clone()
+                };
+                changes.entry(name).or_default().push(change);
+            }
+        }
+
+        Self { changes }
+    }
+
+    /// Add the commits naming one of `names` to `changes`, newest first, skipping the commits
+    /// already there
+    pub fn link_changes(&self, changes: &mut Vec<Change>, names: &[&str]) {
+        let mut linked = false;
+
+        for name in names {
+            let Some(named_changes) = self.changes.get(&normalize_name(name)) else {
+                continue;
+            };
+            for change in named_changes {
+                if !changes.iter().any(|existing| existing.hash == change.hash) {
+                    changes.push(change.clone());
+                    linked = true;
+                }
+            }
+        }
+
+        if linked {
+            changes.sort_by(|a, b| b.date.cmp(&a.date));
+        }
+    }
+}
+
+/// Whether a commit names one of `names` in its message, `names` being normalized with
+/// `normalize_name`
+pub fn names_any_feature(change: &Change, names: &BTreeSet<String>) -> bool {
+    linked_feature_names(change)
+        .iter()
+        .any(|name| names.contains(name))
+}
+
+/// Normalized names of the features a commit names in its `Feature:` trailers and scopes
+fn linked_feature_names(change: &Change) -> Vec<String> {
+    let mut names: Vec<String> = trailers(&change.description)
+        .into_iter()
+        .filter_map(|(key, value)| key.eq_ignore_ascii_case("feature").then_some(value))
+        .chain(conventional_scopes(&change.title))
+        .map(normalize_name)
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Trailers of a commit description: its last paragraph, when every line of it is a
+/// `Key: value` pair, so a `Feature:` line in the body text links nothing
+fn trailers(description: &str) -> Vec<(&str, &str)> {
+    let lines: Vec<&str> = description.lines().map(str::trim).collect();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |index| index + 1);
+    let start = lines[..end]
+        .iter()
+        .rposition(|line| line.is_empty())
+        .map_or(0, |index| index + 1);
+
+    lines[start..end]
+        .iter()
+        .map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let is_token =
+                !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            is_token.then_some((key, value))
+        })
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_default()
+}
+
+/// Scopes of a conventional commit title, e.g. `auth` and `billing` in `feat(auth, billing): ...`
+fn conventional_scopes(title: &str) -> Vec<&str> {
+    if extract_commit_type(title) == "other" {
+        return Vec::new();
+    }
+
+    title
+        .split_once(':')
+        .and_then(|(prefix, _)| prefix.split_once('('))
+        .and_then(|(_, scope)| scope.split_once(')'))
+        .map(|(scopes, _)| scopes.split(',').collect())
+        .unwrap_or_default()
+}
+
+/// Feature name as compared with the names of commit messages
+pub fn normalize_name(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_helper::CommitRecord;
+
+    fn change(hash: &str, title: &str, description: &str, date: &str) -> Change {
+        Change {
+            title: title.to_string(),
+            author_name: "Alice".to_string(),
+            author_email: "alice@example.com".to_string(),
+            description: description.to_string(),
+            date: date.to_string(),
+            hash: hash.to_string(),
+            linked_by_message: false,
+            nested_only: false,
+        }
+    }
+
+    #[test]
+    fn test_linked_feature_names() {
+        let names = |title: &str, description: &str| {
+            linked_feature_names(&change("c1", title, description, ""))
+        };
+
+        assert_eq!(names("feat(checkout): add coupons", ""), vec!["checkout"]);
+        assert_eq!(
+            names("fix(Auth, user_profile)!: reject tokens", ""),
+            vec!["auth", "user-profile"]
+        );
+        assert_eq!(
+            names(
+                "Update shared config",
+                "Details\n\nFeature: Order History\nfeature: checkout"
+            ),
+            vec!["checkout", "order-history"]
+        );
+        // Only the trailer block is read, not a `Feature:` line of the body text
+        assert!(
+            names(
+                "Update shared config",
+                "Feature: flags are now read at startup\n\nSigned-off-by: Alice"
+            )
+            .is_empty()
+        );
+        assert!(names("Update shared config", "Feature: checkout\nis faster").is_empty());
+        // Scopes of titles that are not conventional commits are ignored
+        assert!(names("wip(checkout): test", "").is_empty());
+        assert!(names("feat: no scope", "Refs: #12").is_empty());
+    }
+
+    #[test]
+    fn test_link_changes() {
+        let record = |change: Change| CommitRecord {
+            change,
+            paths: Vec::new(),
+            renames: Vec::new(),
+        };
+        let history = CommitHistory {
+            commits: vec![
+                record(change(
+                    "c3",
+                    "chore: migrate orders",
+                    "Feature: order history",
+                    "2025-03-01 00:00:00",
+                )),
+                record(change(
+                    "c2",
+                    "feat(order-history): paginate",
+                    "",
+                    "2025-02-01 00:00:00",
+                )),
+            ],
+            ..CommitHistory::default()
+        };
+        let links = CommitLinks::from_history(&history);
+
+        let mut changes = vec![
+            change(
+                "c2",
+                "feat(order-history): paginate",
+                "",
+                "2025-02-01 00:00:00",
+            ),
+            change("c1", "feat: add orders", "", "2025-01-01 00:00:00"),
+        ];
+        links.link_changes(&mut changes, &["Order History", "orders"]);
+
+        let hashes: Vec<(&str, bool)> = changes
+            .iter()
+            .map(|change| (change.hash.as_str(), change.linked_by_message))
+            .collect();
+        assert_eq!(hashes, vec![("c3", true), ("c2", false), ("c1", false)]);
+    }
+}
@@ -0,0 +1,491 @@
+//! Module for the project configuration file (`features.config.toml`)
+//!
+//! The configuration file is discovered by walking up from the scanned path and provides
+//! defaults for the command line options and for the lists the scanner uses to classify
+//! directories. Options passed on the command line always take precedence.
+//!
+//! Relative paths are resolved from the folder of the file, but the `patterns` and `exclude`
+//! globs are matched against paths relative to the scanned path, like `--exclude`.
+//!
+//! ```toml
+//! coverage_dir = "reports/coverage"
+//! project_dir = "."
+//! skip_changes = true
+//!
+//! [codeowners]
+//! path = ".github/CODEOWNERS"
+//! prefix = "@"
+//!
+//! [scan]
+//! doc_dirs = ["docs", "__docs__", ".docs"]
+//! skip_dirs = ["node_modules", "target", "dist"]
+//! containers = ["features", "modules"]
+//! patterns = ["**/apps/*"]
+//! exclude = ["generated/", "*.gen.ts"]
+//! respect_gitignore = true
+//! cache = true
+//!
+//! [scan.history]
+//! since = "2024-01-01"
+//! max_commits = 10000
+//! first_parent = true
+//! mailmap = "authors.mailmap"
+//!
+//! [check]
+//! allowed_owners = ["@team-a", "@team-b"]
+//! allowed_statuses = ["experimental", "active", "deprecated"]
+//!
+//! [check.rules]
+//! missing-description = "off"
+//! missing-owner = "error"
+//! ```
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::cache::CACHE_DIR_NAME;
+use crate::checker::Severity;
+
+/// Name of the configuration file looked up in the scanned path and its ancestors
+pub const CONFIG_FILE_NAME: &str = "features.config.toml";
+
+/// Settings of the configuration file, relative paths are resolved against its directory
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Coverage directory to use instead of searching the default locations
+    pub coverage_dir: Option<PathBuf>,
+    /// Project directory for CODEOWNERS paths and additional coverage search locations
+    pub project_dir: Option<PathBuf>,
+    /// Whether to skip computing git history
+    pub skip_changes: Option<bool>,
+    /// CODEOWNERS generation settings
+    pub codeowners: CodeownersSettings,
+    /// Settings of the directory scanner
+    pub scan: ScanSettings,
+    /// Settings of the `check` command
+    pub check: CheckSettings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CodeownersSettings {
+    /// Custom path and filename of the CODEOWNERS file
+    pub path: Option<PathBuf>,
+    /// Prefix added to owner names
+    pub prefix: Option<String>,
+}
+
+/// Settings of the architecture checks
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckSettings {
+    /// Severity of rules by id, overriding their default severity
+    pub rules: BTreeMap<String, Severity>,
+    /// Owners features may be assigned to, any owner is accepted when empty
+    pub allowed_owners: Vec<String>,
+    /// Accepted values of the `status` metadata of features, any status is accepted when
+    /// empty
+    pub allowed_statuses: Vec<String>,
+}
+
+/// Lists used by the scanner to classify directories
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanSettings {
+    /// Names of documentation directories, never treated as features (case-insensitive)
+    pub doc_dirs: Vec<String>,
+    /// Names of directories that are never walked (dependencies, build output...)
+    pub skip_dirs: Vec<String>,
+    /// Names of the folders whose direct subfolders are features
+    pub containers: Vec<String>,
+    /// Globs matching feature folders, relative to the scanned path rather than to the
+    /// configuration file (e.g. `**/apps/*`)
+    pub patterns: Vec<String>,
+    /// Globs of files and directories left out of the scan, in .gitignore syntax, anchored at
+    /// the scanned path rather than at the configuration file
+    pub exclude: Vec<String>,
+    /// Whether `.gitignore`, `.ignore` and git exclude files are honored
+    pub respect_gitignore: bool,
+    /// Whether scan results are kept in `.features-cache/` to speed up the next scans
+    pub cache: bool,
+    /// Limits of the git history read for the features
+    pub history: HistorySettings,
+    /// Git revision scanned instead of the working directory, only set with `--rev`
+    #[serde(skip)]
+    pub revision: Option<String>,
+    /// `patterns` compiled on first use, so they must not change once the scan has started
+    #[serde(skip)]
+    compiled_patterns: OnceLock<GlobSet>,
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            doc_dirs: ["docs", "__docs__", ".docs"].map(String::from).to_vec(),
+            skip_dirs: [
+                "node_modules",
+                "target",
+                "dist",
+                "build",
+                ".git",
+                ".svn",
+                ".hg",
+                "vendor",
+                "__pycache__",
+                ".next",
+                ".nuxt",
+                "coverage",
+                CACHE_DIR_NAME,
+            ]
+            .map(String::from)
+            .to_vec(),
+            containers: vec!["features".to_string()],
+            patterns: Vec::new(),
+            exclude: Vec::new(),
+            respect_gitignore: true,
+            cache: false,
+            history: HistorySettings::default(),
+            revision: None,
+            compiled_patterns: OnceLock::new(),
+        }
+    }
+}
+
+impl ScanSettings {
+    /// Check if a directory name is one of the documentation directories
+    pub fn is_doc_dir(&self, dir_name: &str) -> bool {
+        let dir_name = dir_name.to_lowercase();
+        self.doc_dirs.iter().any(|d| d.to_lowercase() == dir_name)
+    }
+
+    /// Check if a directory name must not be walked
+    pub fn is_skipped_dir(&self, dir_name: &str) -> bool {
+        self.skip_dirs.iter().any(|d| d == dir_name)
+    }
+
+    /// Check if a directory name is one of the feature containers
+    pub fn is_container(&self, dir_name: &str) -> bool {
+        self.containers.iter().any(|c| c == dir_name)
+    }
+
+    /// Check if a directory is a feature by its location: a direct subfolder of a
+    /// container, or a folder matching one of the patterns relative to `base_path`
+    pub fn is_feature_location(&self, dir_path: &Path, base_path: &Path) -> bool {
+        if let Some(parent_name) = dir_path
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .and_then(|name| name.to_str())
+            && self.is_container(parent_name)
+        {
+            return true;
+        }
+
+        if self.patterns.is_empty() {
+            return false;
+        }
+
+        match dir_path.strip_prefix(base_path) {
+            Ok(relative_path) if !relative_path.as_os_str().is_empty() => {
+                let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+                self.pattern_set().is_match(relative_path)
+            }
+            _ => false,
+        }
+    }
+
+    /// Check that every pattern is a valid glob and the history dates can be parsed
+    pub fn validate(&self) -> Result<()> {
+        build_pattern_set(&self.patterns)?;
+        self.history.validate()
+    }
+
+    fn pattern_set(&self) -> &GlobSet {
+        // Invalid patterns are reported by `validate` when the configuration is loaded
+        self.compiled_patterns
+            .get_or_init(|| build_pattern_set(&self.patterns).unwrap_or_default())
+    }
+}
+
+fn build_pattern_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern.trim_end_matches('/'))
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("invalid feature pattern `{}`", pattern))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// Limits of the git history walk, to keep scans of very large repositories fast
+///
+/// Only the commits touching the scanned folder are read in any case.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistorySettings {
+    /// Oldest commit date read, as `YYYY-MM-DD`, `YYYY-MM-DD HH:MM:SS` (UTC) or RFC 3339
+    pub since: Option<String>,
+    /// Newest commit date read, a day without time being included entirely
+    pub until: Option<String>,
+    /// Maximum number of commits read, newest first
+    pub max_commits: Option<usize>,
+    /// Whether only the first parent of merge commits is followed
+    pub first_parent: bool,
+    /// File of author aliases in `.mailmap` format, applied after the repository `.mailmap`
+    pub mailmap: Option<PathBuf>,
+}
+
+impl HistorySettings {
+    /// Check that the dates can be parsed and the alias file exists
+    pub fn validate(&self) -> Result<()> {
+        self.since_timestamp()?;
+        self.until_timestamp()?;
+        if let Some(mailmap) = &self.mailmap
+            && !mailmap.is_file()
+        {
+            anyhow::bail!("could not find mailmap file `{}`", mailmap.display());
+        }
+        Ok(())
+    }
+
+    /// `since` as a unix timestamp
+    pub fn since_timestamp(&self) -> Result<Option<i64>> {
+        self.since
+            .as_deref()
+            .map(|date| parse_date(date, false))
+            .transpose()
+    }
+
+    /// `until` as a unix timestamp
+    pub fn until_timestamp(&self) -> Result<Option<i64>> {
+        self.until
+            .as_deref()
+            .map(|date| parse_date(date, true))
+            .transpose()
+    }
+}
+
+/// Parse a date as a unix timestamp, a day without time starting or ending at midnight UTC
+fn parse_date(value: &str, end_of_day: bool) -> Result<i64> {
+    let value = value.trim();
+
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.timestamp());
+    }
+    if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Ok(datetime.and_utc().timestamp());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let time = if end_of_day {
+            chrono::NaiveTime::from_hms_opt(23, 59, 59)
+        } else {
+            chrono::NaiveTime::from_hms_opt(0, 0, 0)
+        };
+        return Ok(date
+            .and_time(time.unwrap_or_default())
+            .and_utc()
+            .timestamp());
+    }
+
+    anyhow::bail!(
+        "invalid date `{}`, expected YYYY-MM-DD, YYYY-MM-DD HH:MM:SS or RFC 3339",
+        value
+    )
+}
+
+impl ProjectConfig {
+    /// Reads and parses a configuration file, resolving its relative paths
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("could not read config file at `{}`", path.display()))?;
+
+        let mut config: ProjectConfig = toml::from_str(&content)
+            .with_context(|| format!("could not parse config file at `{}`", path.display()))?;
+
+        if let Some(config_dir) = path.parent() {
+            config.resolve_paths(config_dir);
+        }
+        config
+            .scan
+            .validate()
+            .with_context(|| format!("invalid config file at `{}`", path.display()))?;
+
+        Ok(config)
+    }
+
+    /// Loads the configuration file found in `start` or its closest ancestor,
+    /// or the default configuration if there is none
+    pub fn discover(start: &Path) -> Result<Self> {
+        match find_config_file(start) {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn resolve_paths(&mut self, config_dir: &Path) {
+        for path in [
+            &mut self.coverage_dir,
+            &mut self.project_dir,
+            &mut self.codeowners.path,
+            &mut self.scan.history.mailmap,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if path.is_relative() {
+                *path = config_dir.join(&*path);
+            }
+        }
+    }
+}
+
+/// Finds the configuration file in a directory or its closest ancestor
+pub fn find_config_file(start: &Path) -> Option<PathBuf> {
+    let start = fs::canonicalize(start).unwrap_or_else(|_| start.to_path_buf());
+
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_discover_walks_up_to_config_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("src").join("features");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE_NAME),
+            r#"
+coverage_dir = "reports/coverage"
+skip_changes = true
+
+[codeowners]
+prefix = "team:"
+
+[scan]
+doc_dirs = ["documentation"]
+containers = ["modules", "domains"]
+patterns = ["apps/*"]
+
+[scan.history]
+since = "2024-01-01"
+max_commits = 500
+
+[check.rules]
+missing-owner = "error"
+"#,
+        )
+        .unwrap();
+
+        let config = ProjectConfig::discover(&nested).unwrap();
+        let root = fs::canonicalize(temp_dir.path()).unwrap();
+
+        assert_eq!(config.coverage_dir, Some(root.join("reports/coverage")));
+        assert_eq!(config.skip_changes, Some(true));
+        assert_eq!(config.codeowners.prefix, Some("team:".to_string()));
+        assert_eq!(config.scan.doc_dirs, vec!["documentation".to_string()]);
+        assert!(config.scan.is_container("domains"));
+        assert!(!config.scan.is_container("features"));
+        assert_eq!(
+            config.scan.history.since_timestamp().unwrap(),
+            Some(1_704_067_200)
+        );
+        assert_eq!(config.scan.history.max_commits, Some(500));
+        assert!(!config.scan.history.first_parent);
+        assert_eq!(
+            config.check.rules.get("missing-owner"),
+            Some(&Severity::Error)
+        );
+        // Unset lists keep their defaults
+        assert!(config.scan.is_skipped_dir("node_modules"));
+        assert!(config.check.allowed_statuses.is_empty());
+    }
+
+    #[test]
+    fn test_discover_without_config_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = ProjectConfig::discover(temp_dir.path()).unwrap();
+
+        assert!(config.coverage_dir.is_none());
+        assert!(config.scan.is_container("features"));
+        assert!(config.scan.is_doc_dir("Docs"));
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&config_path, "coverage = \"typo\"\n").unwrap();
+
+        assert!(ProjectConfig::load(&config_path).is_err());
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&config_path, "[scan]\npatterns = [\"apps/[*\"]\n").unwrap();
+
+        assert!(ProjectConfig::load(&config_path).is_err());
+    }
+
+    #[test]
+    fn test_history_dates() {
+        let history = HistorySettings {
+            since: Some("2024-01-01".to_string()),
+            until: Some("2024-01-01".to_string()),
+            ..HistorySettings::default()
+        };
+        assert_eq!(history.since_timestamp().unwrap(), Some(1_704_067_200));
+        // A day without time is included entirely
+        assert_eq!(history.until_timestamp().unwrap(), Some(1_704_153_599));
+
+        let history = HistorySettings {
+            since: Some("2024-01-01T02:00:00+02:00".to_string()),
+            until: Some("last week".to_string()),
+            ..HistorySettings::default()
+        };
+        assert_eq!(history.since_timestamp().unwrap(), Some(1_704_067_200));
+        assert!(history.validate().is_err());
+    }
+
+    #[test]
+    fn test_is_feature_location() {
+        let settings = ScanSettings {
+            containers: vec!["features".to_string(), "modules".to_string()],
+            patterns: vec!["apps/*".to_string(), "src/domains/*/".to_string()],
+            ..ScanSettings::default()
+        };
+        let base = Path::new("/repo");
+
+        assert!(settings.is_feature_location(Path::new("/repo/src/features/auth"), base));
+        assert!(settings.is_feature_location(Path::new("/repo/lib/modules/billing"), base));
+        assert!(settings.is_feature_location(Path::new("/repo/apps/web"), base));
+        assert!(settings.is_feature_location(Path::new("/repo/src/domains/orders"), base));
+        assert!(!settings.is_feature_location(Path::new("/repo/apps/web/src"), base));
+        assert!(!settings.is_feature_location(Path::new("/repo/apps"), base));
+        assert!(!settings.is_feature_location(Path::new("/repo/src/features"), base));
+
+        // Patterns are matched from the scanned path, `**/` keeps them independent of it
+        let settings = ScanSettings {
+            patterns: vec!["**/domains/*".to_string()],
+            ..ScanSettings::default()
+        };
+        let orders = Path::new("/repo/src/domains/orders");
+        assert!(settings.is_feature_location(orders, base));
+        assert!(settings.is_feature_location(orders, Path::new("/repo/src")));
+    }
+}
@@ -0,0 +1,160 @@
+//! Module for the knowledge concentration metrics of features
+//!
+//! The own commits of a feature, nested features excluded, are counted by author (after the
+//! `.mailmap` is applied) to tell who knows the feature and how risky it is to lose them. The
+//! bus factor is the smallest number of authors whose commits add up to at least half of the
+//! commits of the feature: a bus factor of 1 means one person wrote most of it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::Change;
+
+/// Number of authors listed in `top_contributors` and `recent_contributors`
+const LISTED_CONTRIBUTORS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContributorStats {
+    /// Smallest number of authors who made at least half of the commits
+    pub bus_factor: usize,
+    /// Names of the authors with the most commits, most commits first
+    pub top_contributors: Vec<String>,
+    /// Names of the authors who committed last, most recent first
+    pub recent_contributors: Vec<String>,
+    /// Every author, most commits first
+    pub authors: Vec<AuthorContribution>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorContribution {
+    pub name: String,
+    /// Email of the most recent commit of the author
+    pub email: String,
+    pub commits: usize,
+    /// Share of the commits of the feature, in percent
+    pub share_percent: f64,
+    pub last_commit_date: String,
+}
+
+/// Contribution of every author to a feature, `None` without commits
+pub fn compute_contributors(changes: &[&Change]) -> Option<ContributorStats> {
+    if changes.is_empty() {
+        return None;
+    }
+
+    // Authors are identified by name, as in the `authors_count` commit stats
+    let mut authors: HashMap<&str, AuthorContribution> = HashMap::new();
+    for change in changes {
+        let author = authors
+            .entry(change.author_name.as_str())
+            .or_insert_with(|| AuthorContribution {
+                name: change.author_name.clone(),
+                email: change.author_email.clone(),
+                commits: 0,
+                share_percent: 0.0,
+                last_commit_date: change.date.clone(),
+            });
+        author.commits += 1;
+        if change.date > author.last_commit_date {
+            author.email = change.author_email.clone();
+            author.last_commit_date = change.date.clone();
+        }
+    }
+
+    let total = changes.len();
+    let mut authors: Vec<AuthorContribution> = authors.into_values().collect();
+    for author in &mut authors {
+        let share = author.commits as f64 * 100.0 / total as f64;
+        author.share_percent = (share * 100.0).round() / 100.0;
+    }
+    authors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+
+    let mut covered = 0;
+    let bus_factor = authors
+        .iter()
+        .take_while(|author| {
+            let below_half = covered * 2 < total;
+            covered += author.commits;
+            below_half
+        })
+        .count();
+
+    let mut recent: Vec<&AuthorContribution> = authors.iter().collect();
+    recent.sort_by(|a, b| {
+        b.last_commit_date
+            .cmp(&a.last_commit_date)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Some(ContributorStats {
+        bus_factor,
+        top_contributors: authors
+            .iter()
+            .take(LISTED_CONTRIBUTORS)
+            .map(|author| author.name.clone())
+            .collect(),
+        recent_contributors: recent
+            .iter()
+            .take(LISTED_CONTRIBUTORS)
+            .map(|author| author.name.clone())
+            .collect(),
+        authors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(author: &str, date: &str) -> Change {
+        Change {
+            title: "feat: update".to_string(),
+            author_name: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            description: String::new(),
+            date: date.to_string(),
+            hash: format!("{}-{}", author, date),
+            linked_by_message: false,
+            nested_only: false,
+        }
+    }
+
+    #[test]
+    fn test_compute_contributors() {
+        let changes = [
+            change("Carol", "2025-03-01 10:00:00"),
+            change("Alice", "2025-02-01 10:00:00"),
+            change("Bob", "2025-01-20 10:00:00"),
+            change("Alice", "2025-01-10 10:00:00"),
+            change("Alice", "2025-01-05 10:00:00"),
+            change("Bob", "2025-01-01 10:00:00"),
+        ];
+        let changes: Vec<&Change> = changes.iter().collect();
+
+        let stats = compute_contributors(&changes).unwrap();
+
+        // Alice made 3 of the 6 commits, which is half of them
+        assert_eq!(stats.bus_factor, 1);
+        assert_eq!(stats.top_contributors, vec!["Alice", "Bob", "Carol"]);
+        assert_eq!(stats.recent_contributors, vec!["Carol", "Alice", "Bob"]);
+        assert_eq!(stats.authors[0].commits, 3);
+        assert_eq!(stats.authors[0].share_percent, 50.0);
+        assert_eq!(stats.authors[0].last_commit_date, "2025-02-01 10:00:00");
+        assert_eq!(stats.authors[2].share_percent, 16.67);
+    }
+
+    #[test]
+    fn test_bus_factor_of_evenly_shared_feature() {
+        let changes = [
+            change("Alice", "2025-01-04 10:00:00"),
+            change("Bob", "2025-01-03 10:00:00"),
+            change("Carol", "2025-01-02 10:00:00"),
+            change("Dave", "2025-01-01 10:00:00"),
+            change("Erin", "2025-01-01 09:00:00"),
+        ];
+        let changes: Vec<&Change> = changes.iter().collect();
+
+        assert_eq!(compute_contributors(&changes).unwrap().bus_factor, 3);
+        assert_eq!(compute_contributors(&[]), None);
+    }
+}
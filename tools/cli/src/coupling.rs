@@ -0,0 +1,309 @@
+//! Module for the temporal coupling of features
+//!
+//! Two features are coupled when they keep changing in the same commits, which reveals
+//! dependencies the imports do not show (shared configuration, duplicated code, API
+//! contracts). Every file of a commit is attributed to its innermost feature, so a parent
+//! feature is only coupled through its own files. The degree of coupling of two features is
+//! the number of commits changing both, divided by the average number of commits of each.
+//!
+//! Commits changing many features at once (formatting, mass renames) are left out, as are
+//! pairs of features changed together only once.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::models::{Feature, flatten_features};
+
+/// Fewest commits changing two features for them to be coupled
+const MIN_CO_CHANGES: usize = 2;
+
+/// Commits changing more features than this are left out
+const MAX_FEATURES_PER_COMMIT: usize = 10;
+
+/// Number of coupled features listed for each feature, strongest first
+const LISTED_COUPLINGS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoupledFeature {
+    pub name: String,
+    pub path: String,
+    /// Commits changing both features
+    pub co_changes: usize,
+    /// Commits changing both features, in percent of the average commits of each
+    pub degree_percent: f64,
+}
+
+/// Coupled features of a feature, as shown by the `coupling` command
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureCouplings {
+    pub name: String,
+    pub path: String,
+    pub coupling: Vec<CoupledFeature>,
+}
+
+/// Add the strongest couplings of every feature to its stats
+///
+/// `commit_paths` are the files changed by each commit, relative to the repository root, and
+/// `subtree` is the scanned folder relative to the same root.
+pub fn add_couplings(
+    features: &mut [Feature],
+    commit_paths: &HashMap<&str, Vec<String>>,
+    subtree: &str,
+) {
+    let mut couplings = compute_couplings(features, commit_paths, subtree);
+    assign_couplings(features, &mut couplings);
+}
+
+fn compute_couplings(
+    features: &[Feature],
+    commit_paths: &HashMap<&str, Vec<String>>,
+    subtree: &str,
+) -> HashMap<String, Vec<CoupledFeature>> {
+    let mut names: HashMap<&str, &str> = HashMap::new();
+    for feature in flatten_features(features) {
+        names.insert(&feature.path, &feature.name);
+    }
+
+    // Longest paths first, so files go to their innermost feature
+    let mut feature_paths: Vec<&str> = names.keys().copied().collect();
+    feature_paths.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+    let mut commits: HashMap<&str, usize> = HashMap::new();
+    let mut co_changes: HashMap<(&str, &str), usize> = HashMap::new();
+
+    for paths in commit_paths.values() {
+        let changed: BTreeSet<&str> = paths
+            .iter()
+            .filter_map(|path| relative_to(path, subtree))
+            .filter_map(|path| {
+                feature_paths
+                    .iter()
+                    .find(|feature_path| is_within(path, feature_path))
+                    .copied()
+            })
+            .collect();
+
+        if changed.len() > MAX_FEATURES_PER_COMMIT {
+            continue;
+        }
+
+        let changed: Vec<&str> = changed.into_iter().collect();
+        for (index, first) in changed.iter().enumerate() {
+            *commits.entry(first).or_default() += 1;
+            for second in &changed[index + 1..] {
+                *co_changes.entry((first, second)).or_default() += 1;
+            }
+        }
+    }
+
+    let mut couplings: HashMap<String, Vec<CoupledFeature>> = HashMap::new();
+    for ((first, second), count) in co_changes {
+        if count < MIN_CO_CHANGES {
+            continue;
+        }
+
+        let average = (commits[first] + commits[second]) as f64 / 2.0;
+        let degree = count as f64 * 100.0 / average;
+        let degree_percent = (degree * 100.0).round() / 100.0;
+
+        for (feature, other) in [(first, second), (second, first)] {
+            couplings
+                .entry(feature.to_string())
+                .or_default()
+                .push(CoupledFeature {
+                    name: names[other].to_string(),
+                    path: other.to_string(),
+                    co_changes: count,
+                    degree_percent,
+                });
+        }
+    }
+
+    for coupled in couplings.values_mut() {
+        coupled.sort_by(|a, b| {
+            b.degree_percent
+                .total_cmp(&a.degree_percent)
+                .then_with(|| b.co_changes.cmp(&a.co_changes))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        coupled.truncate(LISTED_COUPLINGS);
+    }
+
+    couplings
+}
+
+fn assign_couplings(
+    features: &mut [Feature],
+    couplings: &mut HashMap<String, Vec<CoupledFeature>>,
+) {
+    for feature in features {
+        if let Some(stats) = &mut feature.stats
+            && let Some(coupling) = couplings.remove(&feature.path)
+        {
+            stats.coupling = coupling;
+        }
+        assign_couplings(&mut feature.features, couplings);
+    }
+}
+
+/// Path relative to the scanned folder, `None` outside of it
+fn relative_to<'p>(path: &'p str, subtree: &str) -> Option<&'p str> {
+    if subtree.is_empty() {
+        return Some(path);
+    }
+    path.strip_prefix(subtree)?.strip_prefix('/')
+}
+
+fn is_within(path: &str, folder: &str) -> bool {
+    folder.is_empty()
+        || path
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Features with couplings of at least `min_degree` percent, sorted by path
+pub fn collect_couplings(features: &[Feature], min_degree: f64) -> Vec<FeatureCouplings> {
+    let mut result = Vec::new();
+    for feature in flatten_features(features) {
+        let coupling: Vec<CoupledFeature> = feature
+            .stats
+            .iter()
+            .flat_map(|stats| &stats.coupling)
+            .filter(|coupled| coupled.degree_percent >= min_degree)
+            .cloned()
+            .collect();
+        if !coupling.is_empty() {
+            result.push(FeatureCouplings {
+                name: feature.name.clone(),
+                path: feature.path.clone(),
+                coupling,
+            });
+        }
+    }
+
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    result
+}
+
+/// One block per feature listing its coupled features, strongest first
+pub fn format_text(couplings: &[FeatureCouplings]) -> String {
+    let mut output = String::new();
+
+    for feature in couplings {
+        let _ = writeln!(output, "{} ({})", feature.name, feature.path);
+        for coupled in &feature.coupling {
+            let _ = writeln!(
+                output,
+                "  {:>6.2}%  {} commits  {} ({})",
+                coupled.degree_percent, coupled.co_changes, coupled.name, coupled.path
+            );
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Stats;
+
+    fn feature(path: &str, features: Vec<Feature>) -> Feature {
+        Feature {
+            features,
+            stats: Some(Stats::default()),
+            ..Feature::at(path)
+        }
+    }
+
+    fn coupled_paths(feature: &Feature) -> Vec<(&str, usize, f64)> {
+        feature
+            .stats
+            .as_ref()
+            .unwrap()
+            .coupling
+            .iter()
+            .map(|coupled| {
+                let path = coupled.path.as_str();
+                (path, coupled.co_changes, coupled.degree_percent)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_add_couplings() {
+        let mut features = vec![
+            feature(
+                "features/auth",
+                vec![feature("features/auth/features/sso", Vec::new())],
+            ),
+            feature("features/billing", Vec::new()),
+            feature("features/docs", Vec::new()),
+        ];
+
+        let commits = [
+            (
+                "c1",
+                vec!["app/features/auth/login.rs", "app/features/billing/plan.rs"],
+            ),
+            (
+                "c2",
+                vec!["app/features/auth/token.rs", "app/features/billing/plan.rs"],
+            ),
+            ("c3", vec!["app/features/auth/login.rs", "app/config.toml"]),
+            (
+                "c4",
+                vec![
+                    "app/features/auth/features/sso/saml.rs",
+                    "app/features/docs/README.md",
+                ],
+            ),
+            (
+                "c5",
+                vec!["app/features/billing/plan.rs", "other/features/auth/x.rs"],
+            ),
+        ];
+        let commit_paths: HashMap<&str, Vec<String>> = commits
+            .into_iter()
+            .map(|(hash, paths)| (hash, paths.into_iter().map(String::from).collect()))
+            .collect();
+
+        add_couplings(&mut features, &commit_paths, "app");
+
+        // auth changed in 3 commits, billing in 3, and both in 2 of them
+        assert_eq!(
+            coupled_paths(&features[0]),
+            vec![("features/billing", 2, 66.67)]
+        );
+        assert_eq!(
+            coupled_paths(&features[1]),
+            vec![("features/auth", 2, 66.67)]
+        );
+        // Changed together once only
+        assert!(coupled_paths(&features[0].features[0]).is_empty());
+        assert!(coupled_paths(&features[2]).is_empty());
+
+        let couplings = collect_couplings(&features, 50.0);
+        assert_eq!(couplings.len(), 2);
+        assert_eq!(couplings[0].path, "features/auth");
+        assert!(collect_couplings(&features, 70.0).is_empty());
+        assert_eq!(
+            format_text(&couplings[..1]),
+            "auth (features/auth)\n   66.67%  2 commits  billing (features/billing)\n"
+        );
+    }
+
+    #[test]
+    fn test_large_commits_are_left_out() {
+        let mut features: Vec<Feature> = (0..12)
+            .map(|index| feature(&format!("f{}", index), Vec::new()))
+            .collect();
+        let paths: Vec<String> = (0..12).map(|index| format!("f{}/lib.rs", index)).collect();
+        let commit_paths = HashMap::from([("c1", paths.clone()), ("c2", paths)]);
+
+        add_couplings(&mut features, &commit_paths, "");
+
+        assert!(collect_couplings(&features, 0.0).is_empty());
+    }
+}
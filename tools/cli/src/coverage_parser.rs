@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::feature_detection::find_feature_for_path;
 use crate::models::Feature;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -558,39 +559,7 @@ fn find_feature_for_file(
     features: &[Feature],
     _canonical_base: Option<&Path>,
 ) -> Option<String> {
-    let normalized_file = normalize_path(file_path);
-
-    fn search_features(normalized_file: &str, features: &[Feature]) -> Option<String> {
-        for feature in features {
-            let normalized_feature = normalize_path(&feature.path);
-
-            // Check if file is in this feature's folder (with proper path boundary)
-            if normalized_file.starts_with(&normalized_feature) {
-                let is_exact_match = normalized_file.len() == normalized_feature.len();
-                let has_path_separator = normalized_file
-                    .get(normalized_feature.len()..normalized_feature.len() + 1)
-                    == Some("/");
-
-                if is_exact_match || has_path_separator {
-                    // Recursively check nested features first (more specific)
-                    if let Some(nested) = search_features(normalized_file, &feature.features) {
-                        return Some(nested);
-                    }
-                    return Some(feature.path.clone());
-                }
-            }
-        }
-        None
-    }
-
-    search_features(&normalized_file, features)
-}
-
-/// Normalize a path by removing leading ./ and converting to forward slashes
-fn normalize_path(path: &str) -> String {
-    let path = path.trim_start_matches("./");
-    let path = path.replace('\\', "/");
-    path.to_string()
+    find_feature_for_path(file_path, features).map(|feature| feature.path.clone())
 }
 
 #[cfg(test)]
@@ -3,8 +3,12 @@
 //! This module takes import statements and determines which features they belong to,
 //! and what type of relationship exists between features (parent, child, sibling).
 
+use crate::cache::ScanCache;
+use crate::config::ScanSettings;
+use crate::feature_detection::is_feature_directory;
 use crate::import_detector::{ImportStatement, resolve_import_path};
 use crate::models::{Dependency, DependencyType};
+use crate::walker::FileTree;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -19,6 +23,9 @@ pub struct FeatureInfo {
 pub fn build_file_to_feature_map(
     features: &[FeatureInfo],
     base_path: &Path,
+    tree: &FileTree,
+    settings: &ScanSettings,
+    cache: &ScanCache,
 ) -> HashMap<PathBuf, String> {
     let mut map = HashMap::new();
 
@@ -36,8 +43,15 @@ pub fn build_file_to_feature_map(
         let feature_path = base_path.join(&feature.path);
 
         // Map all files within this feature directory using the feature's path as identifier
-        if std::fs::read_dir(&feature_path).is_ok() {
-            map_directory_files(&feature_path, &feature.path.to_string_lossy(), &mut map);
+        if tree.is_dir(&feature_path) {
+            map_directory_files(
+                &feature_path,
+                &feature.path.to_string_lossy(),
+                &mut map,
+                tree,
+                settings,
+                cache,
+            );
         }
     }
 
@@ -46,62 +60,32 @@ pub fn build_file_to_feature_map(
 
 /// Recursively map all files in a directory to a feature path
 /// Only maps files that haven't been mapped yet (most specific feature wins)
-fn map_directory_files(dir: &Path, feature_path: &str, map: &mut HashMap<PathBuf, String>) {
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-
-            if path.is_file() {
-                // Canonicalize the path to resolve .. and .
-                let canonical_path = if let Ok(canonical) = path.canonicalize() {
-                    canonical
-                } else {
-                    path.clone()
-                };
-
-                // Only insert if not already mapped (most specific feature takes precedence)
-                map.entry(canonical_path)
-                    .or_insert_with(|| feature_path.to_string());
-            } else if path.is_dir()
-                && let Some(dir_name) = path.file_name().and_then(|n| n.to_str())
-                && !should_skip_directory(dir_name)
-                && !is_nested_feature_directory(&path)
-            {
-                map_directory_files(&path, feature_path, map);
-            }
+fn map_directory_files(
+    dir: &Path,
+    feature_path: &str,
+    map: &mut HashMap<PathBuf, String>,
+    tree: &FileTree,
+    settings: &ScanSettings,
+    cache: &ScanCache,
+) {
+    for path in tree.read_dir(dir) {
+        if tree.is_file(path) {
+            // Canonicalize the path to resolve .. and .
+            let canonical_path = if let Ok(canonical) = tree.source().canonicalize(path) {
+                canonical
+            } else {
+                path.clone()
+            };
+
+            // Only insert if not already mapped (most specific feature takes precedence)
+            map.entry(canonical_path)
+                .or_insert_with(|| feature_path.to_string());
+        } else if tree.is_dir(path) && !is_feature_directory(path, tree, settings, cache) {
+            map_directory_files(path, feature_path, map, tree, settings, cache);
         }
     }
 }
 
-/// Check if a directory is a nested feature directory
-/// A directory is considered a feature if:
-/// 1. It's a direct child of a "features" directory, OR
-/// 2. It has a features.toml file
-fn is_nested_feature_directory(dir: &Path) -> bool {
-    // Check for features.toml
-    if dir.join("features.toml").exists() {
-        return true;
-    }
-
-    // Check if it's a direct child of a "features" directory
-    if let Some(parent) = dir.parent()
-        && let Some(parent_name) = parent.file_name()
-        && parent_name == "features"
-    {
-        return true;
-    }
-
-    false
-}
-
-/// Check if a directory should be skipped
-fn should_skip_directory(dir_name: &str) -> bool {
-    matches!(
-        dir_name,
-        "node_modules" | "target" | "dist" | "build" | ".git" | "__pycache__" | "coverage"
-    )
-}
-
 /// Determine the relationship type between two features based on their paths
 pub fn determine_dependency_type(
     source_feature_path: &Path,
@@ -121,16 +105,18 @@ pub fn determine_dependency_type(
     DependencyType::Sibling
 }
 
-/// Resolve imports to dependencies for a specific feature
+/// Resolve imports to dependencies for a specific feature, `tree` being the scanned folder
 pub fn resolve_feature_dependencies(
     _feature_name: &str,
     feature_path: &Path,
-    base_path: &Path,
+    tree: &FileTree,
     imports: &[ImportStatement],
     file_to_feature_map: &HashMap<PathBuf, String>,
     feature_path_to_name_map: &HashMap<String, String>,
     file_map: &HashMap<String, PathBuf>,
 ) -> Vec<Dependency> {
+    let base_path = tree.root();
+    let source = tree.source();
     let mut dependencies = Vec::new();
     let mut seen = std::collections::HashSet::new();
 
@@ -138,9 +124,13 @@ pub fn resolve_feature_dependencies(
         let source_file = Path::new(&import.file_path);
 
         // Resolve the import to an actual file path
-        if let Some(resolved_path) =
-            resolve_import_path(&import.imported_path, source_file, base_path, file_map)
-        {
+        if let Some(resolved_path) = resolve_import_path(
+            &import.imported_path,
+            source_file,
+            base_path,
+            file_map,
+            source,
+        ) {
             // Find which feature this file belongs to (returns feature path)
             if let Some(target_feature_path_str) = file_to_feature_map.get(&resolved_path) {
                 // Skip if it's the same feature
@@ -176,10 +166,10 @@ pub fn resolve_feature_dependencies(
 
                     // Convert source file path to be relative to base_path
                     let relative_source_filename = if let Ok(canonical_base) =
-                        base_path.canonicalize()
+                        source.canonicalize(base_path)
                     {
                         let source_path = Path::new(&import.file_path);
-                        if let Ok(canonical_source) = source_path.canonicalize() {
+                        if let Ok(canonical_source) = source.canonicalize(source_path) {
                             if let Ok(rel_path) = canonical_source.strip_prefix(&canonical_base) {
                                 rel_path.to_string_lossy().to_string()
                             } else {
@@ -194,7 +184,7 @@ pub fn resolve_feature_dependencies(
 
                     // Convert target file path to be relative to base_path
                     let relative_target_filename =
-                        if let Ok(canonical_base) = base_path.canonicalize() {
+                        if let Ok(canonical_base) = source.canonicalize(base_path) {
                             if let Ok(rel_path) = resolved_path.strip_prefix(&canonical_base) {
                                 rel_path.to_string_lossy().to_string()
                             } else {
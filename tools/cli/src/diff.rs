@@ -0,0 +1,625 @@
+//! Module for the `diff` command
+//!
+//! Two feature reports, read from `features list --json` files or scanned from git revisions,
+//! are compared feature by feature. Features are matched by path, and a feature removed from
+//! one path and added at another is reported as moved rather than as a removal plus an
+//! addition when both have the same name, or else when the added one has the whole history
+//! of the removed one (the history follows renamed files). Line counts and coverage are only
+//! compared when both reports have them.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::models::{Feature, flatten_features};
+
+/// Output format of the diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffFormat {
+    #[default]
+    Text,
+    Json,
+    Markdown,
+}
+
+impl FromStr for DiffFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(DiffFormat::Text),
+            "json" => Ok(DiffFormat::Json),
+            "markdown" => Ok(DiffFormat::Markdown),
+            _ => anyhow::bail!("invalid format `{}`, expected text, json or markdown", s),
+        }
+    }
+}
+
+/// Differences between two feature reports
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FeaturesDiff {
+    pub added: Vec<FeatureSummary>,
+    pub removed: Vec<FeatureSummary>,
+    pub moved: Vec<MovedFeature>,
+    /// Features found in both reports (moved ones included) that changed
+    pub changed: Vec<FeatureChanges>,
+}
+
+impl FeaturesDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureSummary {
+    pub name: String,
+    pub path: String,
+    pub owner: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MovedFeature {
+    pub name: String,
+    pub old_path: String,
+    pub new_path: String,
+}
+
+/// A value in the old and in the new report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueChange<T> {
+    pub old: T,
+    pub new: T,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetaChange {
+    pub key: String,
+    /// `None` when the key was added
+    pub old: Option<Value>,
+    /// `None` when the key was removed
+    pub new: Option<Value>,
+}
+
+/// Changes of a feature, identified by its path and name in the new report
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureChanges {
+    pub name: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<ValueChange<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub meta: Vec<MetaChange>,
+    /// Paths of the features it now depends on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies_added: Vec<String>,
+    /// Paths of the features it no longer depends on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies_removed: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<ValueChange<usize>>,
+    /// Line coverage in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<ValueChange<f64>>,
+}
+
+impl FeatureChanges {
+    fn is_empty(&self) -> bool {
+        self.renamed_from.is_none()
+            && self.owner.is_none()
+            && self.meta.is_empty()
+            && self.dependencies_added.is_empty()
+            && self.dependencies_removed.is_empty()
+            && self.lines.is_none()
+            && self.coverage.is_none()
+    }
+}
+
+/// Read a report written by `features list --json`, nested or `--flat`
+pub fn read_report(path: &Path) -> Result<Vec<Feature>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read features file `{}`", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("could not parse features file `{}`", path.display()))
+}
+
+/// Compare two reports, `old` being the reference
+pub fn diff_features(old: &[Feature], new: &[Feature]) -> FeaturesDiff {
+    let old_features = features_by_path(old);
+    let new_features = features_by_path(new);
+
+    let mut removed: Vec<&Feature> = old_features
+        .values()
+        .filter(|feature| !new_features.contains_key(feature.path.as_str()))
+        .copied()
+        .collect();
+    let mut added: Vec<&Feature> = new_features
+        .values()
+        .filter(|feature| !old_features.contains_key(feature.path.as_str()))
+        .copied()
+        .collect();
+
+    // Names are matched first so a history shared with a renamed feature cannot take over
+    let mut moves: Vec<(&Feature, &Feature)> = Vec::new();
+    for find_target in [find_same_name, find_same_history] {
+        removed.retain(|old_feature| match find_target(old_feature, &added) {
+            Some(index) => {
+                moves.push((old_feature, added.remove(index)));
+                false
+            }
+            None => true,
+        });
+    }
+    moves.sort_by(|a, b| a.1.path.cmp(&b.1.path));
+
+    // Dependencies on moved features are compared by their new path
+    let new_paths: HashMap<&str, &str> = moves
+        .iter()
+        .map(|(old_feature, new_feature)| (old_feature.path.as_str(), new_feature.path.as_str()))
+        .collect();
+
+    let mut pairs: Vec<(&Feature, &Feature)> = new_features
+        .values()
+        .filter_map(|new_feature| {
+            old_features
+                .get(new_feature.path.as_str())
+                .map(|old_feature| (*old_feature, *new_feature))
+        })
+        .chain(moves.iter().copied())
+        .collect();
+    pairs.sort_by(|a, b| a.1.path.cmp(&b.1.path));
+
+    FeaturesDiff {
+        added: added.into_iter().map(summarize).collect(),
+        removed: removed.into_iter().map(summarize).collect(),
+        moved: moves
+            .iter()
+            .map(|(old_feature, new_feature)| MovedFeature {
+                name: new_feature.name.clone(),
+                old_path: old_feature.path.clone(),
+                new_path: new_feature.path.clone(),
+            })
+            .collect(),
+        changed: pairs
+            .into_iter()
+            .map(|(old_feature, new_feature)| compare(old_feature, new_feature, &new_paths))
+            .filter(|changes| !changes.is_empty())
+            .collect(),
+    }
+}
+
+/// Every feature of a report, nested ones included, sorted by path
+fn features_by_path(features: &[Feature]) -> BTreeMap<&str, &Feature> {
+    flatten_features(features)
+        .into_iter()
+        .map(|feature| (feature.path.as_str(), feature))
+        .collect()
+}
+
+fn find_same_name(old: &Feature, added: &[&Feature]) -> Option<usize> {
+    added.iter().position(|new| new.name == old.name)
+}
+
+/// The added feature with every commit of `old`, the one with the fewest commits when
+/// nested features or their parents have them all too
+fn find_same_history(old: &Feature, added: &[&Feature]) -> Option<usize> {
+    if old.changes.is_empty() {
+        return None;
+    }
+
+    added
+        .iter()
+        .enumerate()
+        .filter(|(_, new)| {
+            let hashes: HashSet<&str> = new.changes.iter().map(|c| c.hash.as_str()).collect();
+            old.changes.iter().all(|c| hashes.contains(c.hash.as_str()))
+        })
+        .min_by_key(|(_, new)| new.changes.len())
+        .map(|(index, _)| index)
+}
+
+fn summarize(feature: &Feature) -> FeatureSummary {
+    FeatureSummary {
+        name: feature.name.clone(),
+        path: feature.path.clone(),
+        owner: feature.owner.clone(),
+    }
+}
+
+fn compare(old: &Feature, new: &Feature, new_paths: &HashMap<&str, &str>) -> FeatureChanges {
+    let old_dependencies: BTreeSet<&str> = old
+        .dependencies
+        .iter()
+        .map(|dependency| {
+            let path = dependency.feature_path.as_str();
+            new_paths.get(path).copied().unwrap_or(path)
+        })
+        .collect();
+    let new_dependencies: BTreeSet<&str> = new
+        .dependencies
+        .iter()
+        .map(|dependency| dependency.feature_path.as_str())
+        .collect();
+
+    let keys: BTreeSet<&String> = old.meta.keys().chain(new.meta.keys()).collect();
+    let meta = keys
+        .into_iter()
+        .filter(|key| old.meta.get(*key) != new.meta.get(*key))
+        .map(|key| MetaChange {
+            key: key.clone(),
+            old: old.meta.get(key).cloned(),
+            new: new.meta.get(key).cloned(),
+        })
+        .collect();
+
+    let lines = |feature: &Feature| feature.stats.as_ref()?.lines_count;
+    let coverage = |feature: &Feature| {
+        let coverage = feature.stats.as_ref()?.coverage.as_ref()?;
+        Some(coverage.line_coverage_percent)
+    };
+
+    FeatureChanges {
+        name: new.name.clone(),
+        path: new.path.clone(),
+        renamed_from: (old.name != new.name).then(|| old.name.clone()),
+        owner: changed(Some(old.owner.clone()), Some(new.owner.clone())),
+        meta,
+        dependencies_added: new_dependencies
+            .difference(&old_dependencies)
+            .map(|path| path.to_string())
+            .collect(),
+        dependencies_removed: old_dependencies
+            .difference(&new_dependencies)
+            .map(|path| path.to_string())
+            .collect(),
+        lines: changed(lines(old), lines(new)),
+        coverage: changed(coverage(old), coverage(new)),
+    }
+}
+
+/// The change of a value known in both reports, if it changed
+fn changed<T: PartialEq>(old: Option<T>, new: Option<T>) -> Option<ValueChange<T>> {
+    match (old, new) {
+        (Some(old), Some(new)) if old != new => Some(ValueChange { old, new }),
+        _ => None,
+    }
+}
+
+/// One section per kind of difference, changed features being detailed line by line
+pub fn format_text(diff: &FeaturesDiff) -> String {
+    let mut output = String::new();
+
+    if !diff.added.is_empty() {
+        output.push_str("Added features:\n");
+        for feature in &diff.added {
+            let _ = writeln!(
+                output,
+                "  + {} ({}){}",
+                feature.name,
+                feature.path,
+                owned_by(feature)
+            );
+        }
+    }
+    if !diff.removed.is_empty() {
+        output.push_str("Removed features:\n");
+        for feature in &diff.removed {
+            let _ = writeln!(output, "  - {} ({})", feature.name, feature.path);
+        }
+    }
+    if !diff.moved.is_empty() {
+        output.push_str("Moved features:\n");
+        for feature in &diff.moved {
+            let _ = writeln!(
+                output,
+                "  ~ {}: {} -> {}",
+                feature.name, feature.old_path, feature.new_path
+            );
+        }
+    }
+    if !diff.changed.is_empty() {
+        output.push_str("Changed features:\n");
+        for changes in &diff.changed {
+            let _ = writeln!(output, "  {} ({})", changes.name, changes.path);
+            for line in describe_changes(changes, false) {
+                let _ = writeln!(output, "    {}", line);
+            }
+        }
+    }
+
+    output
+}
+
+/// Sections of bullet lists, to paste in release notes or pull request descriptions
+pub fn format_markdown(diff: &FeaturesDiff) -> String {
+    let mut output = String::from("## Features changes\n");
+
+    if diff.is_empty() {
+        output.push_str("\nNo feature changes.\n");
+        return output;
+    }
+
+    if !diff.added.is_empty() {
+        output.push_str("\n### Added\n\n");
+        for feature in &diff.added {
+            let _ = writeln!(
+                output,
+                "- **{}** `{}`{}",
+                feature.name,
+                feature.path,
+                owned_by(feature)
+            );
+        }
+    }
+    if !diff.removed.is_empty() {
+        output.push_str("\n### Removed\n\n");
+        for feature in &diff.removed {
+            let _ = writeln!(output, "- **{}** `{}`", feature.name, feature.path);
+        }
+    }
+    if !diff.moved.is_empty() {
+        output.push_str("\n### Moved\n\n");
+        for feature in &diff.moved {
+            let _ = writeln!(
+                output,
+                "- **{}** `{}` → `{}`",
+                feature.name, feature.old_path, feature.new_path
+            );
+        }
+    }
+    if !diff.changed.is_empty() {
+        output.push_str("\n### Changed\n");
+        for changes in &diff.changed {
+            let _ = writeln!(output, "\n**{}** `{}`\n", changes.name, changes.path);
+            for line in describe_changes(changes, true) {
+                let _ = writeln!(output, "- {}", line);
+            }
+        }
+    }
+
+    output
+}
+
+fn owned_by(feature: &FeatureSummary) -> String {
+    if feature.owner.is_empty() {
+        String::new()
+    } else {
+        format!(" owned by {}", feature.owner)
+    }
+}
+
+/// One line per change of a feature, with code spans in Markdown
+fn describe_changes(changes: &FeatureChanges, markdown: bool) -> Vec<String> {
+    let code = |text: &str| {
+        if markdown {
+            format!("`{}`", text)
+        } else {
+            text.to_string()
+        }
+    };
+    let arrow = if markdown { "→" } else { "->" };
+    let mut lines = Vec::new();
+
+    if let Some(old_name) = &changes.renamed_from {
+        lines.push(format!("renamed from {}", old_name));
+    }
+    if let Some(owner) = &changes.owner {
+        let owner_name = |owner: &str| {
+            if owner.is_empty() {
+                "(none)".to_string()
+            } else {
+                owner.to_string()
+            }
+        };
+        lines.push(format!(
+            "owner: {} {} {}",
+            owner_name(&owner.old),
+            arrow,
+            owner_name(&owner.new)
+        ));
+    }
+    for meta in &changes.meta {
+        let line = match (&meta.old, &meta.new) {
+            (Some(old), Some(new)) => format!(
+                "{}: {} {} {}",
+                code(&meta.key),
+                code(&old.to_string()),
+                arrow,
+                code(&new.to_string())
+            ),
+            (None, Some(new)) => format!("{} added: {}", code(&meta.key), code(&new.to_string())),
+            (Some(_), None) => format!("{} removed", code(&meta.key)),
+            (None, None) => continue,
+        };
+        lines.push(line);
+    }
+    for path in &changes.dependencies_added {
+        lines.push(format!("now depends on {}", code(path)));
+    }
+    for path in &changes.dependencies_removed {
+        lines.push(format!("no longer depends on {}", code(path)));
+    }
+    if let Some(lines_count) = &changes.lines {
+        let delta = lines_count.new as i64 - lines_count.old as i64;
+        lines.push(format!(
+            "lines: {} {} {} ({:+})",
+            lines_count.old, arrow, lines_count.new, delta
+        ));
+    }
+    if let Some(coverage) = &changes.coverage {
+        lines.push(format!(
+            "coverage: {:.1}% {} {:.1}% ({:+.1})",
+            coverage.old,
+            arrow,
+            coverage.new,
+            coverage.new - coverage.old
+        ));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Change, Dependency, DependencyType, Stats};
+
+    fn feature(name: &str, path: &str, owner: &str) -> Feature {
+        Feature {
+            name: name.to_string(),
+            owner: owner.to_string(),
+            ..Feature::at(path)
+        }
+    }
+
+    fn depends_on(mut feature: Feature, target: &str) -> Feature {
+        feature.dependencies.push(Dependency {
+            source_filename: format!("{}/index.ts", feature.path),
+            target_filename: format!("{}/index.ts", target),
+            line: 1,
+            content: String::new(),
+            feature_path: target.to_string(),
+            dependency_type: DependencyType::Sibling,
+        });
+        feature
+    }
+
+    fn with_lines(mut feature: Feature, lines: usize) -> Feature {
+        feature.stats = Some(Stats {
+            lines_count: Some(lines),
+            ..Stats::default()
+        });
+        feature
+    }
+
+    #[test]
+    fn test_diff_features() {
+        let mut checkout = depends_on(
+            with_lines(feature("Cart", "features/checkout", "team-a"), 100),
+            "features/auth",
+        );
+        checkout
+            .meta
+            .insert("status".to_string(), Value::from("beta"));
+        let old = vec![
+            feature("Auth", "features/auth", "team-a"),
+            checkout,
+            feature("Legacy", "features/legacy", "team-b"),
+        ];
+
+        let mut checkout = depends_on(
+            depends_on(
+                with_lines(feature("Checkout", "features/checkout", "team-b"), 160),
+                "core/auth",
+            ),
+            "features/payments",
+        );
+        checkout
+            .meta
+            .insert("status".to_string(), Value::from("stable"));
+        let mut core = feature("Core", "core", "team-a");
+        core.features = vec![feature("Auth", "core/auth", "team-a")];
+        let new = vec![
+            core,
+            checkout,
+            feature("Payments", "features/payments", "team-c"),
+        ];
+
+        let diff = diff_features(&old, &new);
+
+        let paths = |features: &[FeatureSummary]| -> Vec<String> {
+            features.iter().map(|f| f.path.clone()).collect()
+        };
+        assert_eq!(paths(&diff.added), vec!["core", "features/payments"]);
+        assert_eq!(paths(&diff.removed), vec!["features/legacy"]);
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].old_path, "features/auth");
+        assert_eq!(diff.moved[0].new_path, "core/auth");
+
+        assert_eq!(diff.changed.len(), 1);
+        let changes = &diff.changed[0];
+        assert_eq!(changes.renamed_from.as_deref(), Some("Cart"));
+        assert_eq!(
+            changes.owner,
+            Some(ValueChange {
+                old: "team-a".to_string(),
+                new: "team-b".to_string()
+            })
+        );
+        assert_eq!(changes.meta.len(), 1);
+        assert_eq!(changes.meta[0].key, "status");
+        // The dependency on the moved feature is kept
+        assert_eq!(changes.dependencies_added, vec!["features/payments"]);
+        assert!(changes.dependencies_removed.is_empty());
+        assert_eq!(changes.lines, Some(ValueChange { old: 100, new: 160 }));
+        assert_eq!(changes.coverage, None);
+
+        let text = format_text(&diff);
+        assert!(text.contains("  + Payments (features/payments) owned by team-c\n"));
+        assert!(text.contains("  ~ Auth: features/auth -> core/auth\n"));
+        assert!(text.contains("    lines: 100 -> 160 (+60)\n"));
+
+        let markdown = format_markdown(&diff);
+        assert!(markdown.contains("- `status`: `\"beta\"` → `\"stable\"`\n"));
+        assert!(markdown.contains("- now depends on `features/payments`\n"));
+    }
+
+    #[test]
+    fn test_renamed_feature_is_matched_by_history() {
+        let with_commits = |mut feature: Feature, hashes: &[&str]| {
+            feature.changes = hashes
+                .iter()
+                .map(|hash| Change {
+                    title: "feat: update".to_string(),
+                    author_name: "Alice".to_string(),
+                    author_email: "alice@example.com".to_string(),
+                    description: String::new(),
+                    date: "2025-01-01 00:00:00".to_string(),
+                    hash: hash.to_string(),
+                    linked_by_message: false,
+                    nested_only: false,
+                })
+                .collect();
+            feature
+        };
+        let old = vec![with_commits(
+            feature("Login", "features/login", ""),
+            &["a1"],
+        )];
+        let mut core = with_commits(feature("Core", "core", ""), &["a3", "a2", "a1", "a0"]);
+        core.features = vec![with_commits(
+            feature("Auth", "core/auth", ""),
+            &["a2", "a1"],
+        )];
+        let new = vec![core];
+
+        let diff = diff_features(&old, &new);
+
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].old_path, "features/login");
+        assert_eq!(diff.moved[0].new_path, "core/auth");
+        assert_eq!(diff.changed[0].renamed_from.as_deref(), Some("Login"));
+        assert_eq!(diff.added.len(), 1);
+        assert!(diff.removed.is_empty());
+    }
+
+    #[test]
+    fn test_identical_reports_have_no_differences() {
+        let features = vec![with_lines(feature("Auth", "features/auth", "team-a"), 10)];
+
+        let diff = diff_features(&features, &features);
+
+        assert!(diff.is_empty());
+        assert_eq!(format_text(&diff), "");
+        assert!(format_markdown(&diff).contains("No feature changes."));
+    }
+}
@@ -0,0 +1,228 @@
+//! Module deciding where features start and end
+//!
+//! Every subsystem that needs to know whether a folder is a feature, or which feature a
+//! file belongs to, goes through this module so the scanner, the dependency map, the
+//! coverage mapper and the owner lookup agree on feature boundaries.
+//!
+//! A folder is a feature when it is not a documentation folder and:
+//! - it is a direct subfolder of a container (`features/` by default) or matches a pattern, or
+//! - it contains a `FEATURES.toml` (or `features.toml`) file, or
+//! - its README has `feature: true` in its front matter.
+
+use std::path::{Path, PathBuf};
+
+use crate::cache::ScanCache;
+use crate::config::ScanSettings;
+use crate::features_toml_parser::FEATURES_TOML_NAMES;
+use crate::models::Feature;
+use crate::walker::{FileTree, Source};
+
+/// File names of a README, in lookup order
+pub const README_NAMES: [&str; 2] = ["README.md", "README.mdx"];
+
+/// Check if a directory is one of the documentation directories
+pub fn is_documentation_directory(dir_path: &Path, settings: &ScanSettings) -> bool {
+    let dir_name = dir_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+
+    settings.is_doc_dir(dir_name)
+}
+
+/// Check if any parent directory is a documentation directory
+pub fn is_inside_documentation_directory(dir_path: &Path, settings: &ScanSettings) -> bool {
+    dir_path
+        .ancestors()
+        .skip(1)
+        .any(|ancestor| is_documentation_directory(ancestor, settings))
+}
+
+/// Finds the README file of a directory
+pub fn find_readme_file(dir_path: &Path, source: &Source) -> Option<PathBuf> {
+    README_NAMES
+        .iter()
+        .map(|name| dir_path.join(name))
+        .find(|path| source.exists(path))
+}
+
+/// Check if a file is a README, by name
+pub fn is_readme_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| README_NAMES.contains(&name))
+}
+
+/// Check if the content of a README has `feature: true` in its front matter
+pub fn has_feature_flag(content: &str) -> bool {
+    // Check if content starts with YAML front matter (---)
+    if let Some(stripped) = content.strip_prefix("---\n")
+        && let Some(end_pos) = stripped.find("\n---\n")
+    {
+        let yaml_content = &stripped[..end_pos];
+
+        // Parse YAML front matter
+        if let Ok(yaml_value) = serde_yaml::from_str::<serde_yaml::Value>(yaml_content)
+            && let Some(mapping) = yaml_value.as_mapping()
+        {
+            // Check for feature: true
+            if let Some(feature_value) =
+                mapping.get(serde_yaml::Value::String("feature".to_string()))
+            {
+                return feature_value.as_bool() == Some(true);
+            }
+        }
+    }
+    false
+}
+
+/// Check if a directory of the walked tree should be treated as a feature
+///
+/// Feature patterns are matched relative to the root of the tree. The markers are looked up
+/// in the tree, and the README front matter comes from the file summaries of the scan.
+pub fn is_feature_directory(
+    dir_path: &Path,
+    tree: &FileTree,
+    settings: &ScanSettings,
+    cache: &ScanCache,
+) -> bool {
+    // Skip documentation directories
+    if is_documentation_directory(dir_path, settings)
+        || is_inside_documentation_directory(dir_path, settings)
+    {
+        return false;
+    }
+
+    settings.is_feature_location(dir_path, tree.root())
+        || FEATURES_TOML_NAMES
+            .iter()
+            .any(|name| tree.is_file(&dir_path.join(name)))
+        || README_NAMES
+            .iter()
+            .map(|name| dir_path.join(name))
+            .find(|path| tree.is_file(path))
+            .is_some_and(|readme_path| cache.file_summary(&readme_path).feature_flag)
+}
+
+/// Find the innermost feature containing a path relative to the scanned folder
+pub fn find_feature_for_path<'a>(
+    relative_path: &str,
+    features: &'a [Feature],
+) -> Option<&'a Feature> {
+    let normalized_path = normalize_path(relative_path);
+
+    for feature in features {
+        let normalized_feature = normalize_path(&feature.path);
+
+        // Check if the path is in this feature's folder (with proper path boundary)
+        let is_inside = match normalized_path.strip_prefix(&normalized_feature) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        };
+
+        if is_inside {
+            // Nested features are more specific
+            return find_feature_for_path(&normalized_path, &feature.features).or(Some(feature));
+        }
+    }
+
+    None
+}
+
+/// Normalize a path by removing leading ./ and converting to forward slashes
+pub fn normalize_path(path: &str) -> String {
+    let path = path.trim_start_matches("./");
+    path.replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn feature(path: &str, features: Vec<Feature>) -> Feature {
+        Feature {
+            name: path.to_string(),
+            features,
+            ..Feature::at(path)
+        }
+    }
+
+    #[test]
+    fn test_is_feature_directory_markers() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        for dir in [
+            "src/features/auth",
+            "lib/billing",
+            "lib/payments",
+            "lib/plain",
+            "lib/search",
+            "src/features/auth/docs",
+        ] {
+            fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        fs::write(
+            base.join("lib/billing/FEATURES.toml"),
+            "name = \"Billing\"\n",
+        )
+        .unwrap();
+        fs::write(
+            base.join("lib/payments/features.toml"),
+            "owner = \"team\"\n",
+        )
+        .unwrap();
+        fs::write(
+            base.join("src/features/auth/docs/FEATURES.toml"),
+            "name = \"Docs\"\n",
+        )
+        .unwrap();
+
+        fs::write(
+            base.join("lib/search/README.md"),
+            "---\nfeature: true\n---\n# Search\n",
+        )
+        .unwrap();
+        fs::write(
+            base.join("lib/plain/README.md"),
+            "---\nfeature: false\n---\n# Plain\n",
+        )
+        .unwrap();
+
+        let settings = ScanSettings::default();
+        let tree = FileTree::walk(base, &settings).unwrap();
+        let cache = ScanCache::in_memory(&tree);
+        let is_feature =
+            |dir: &str| is_feature_directory(&base.join(dir), &tree, &settings, &cache);
+
+        assert!(is_feature("src/features/auth"));
+        assert!(is_feature("lib/billing"));
+        assert!(is_feature("lib/payments"));
+        assert!(is_feature("lib/search"));
+        assert!(!is_feature("lib/plain"));
+        assert!(!is_feature("src/features/auth/docs"));
+    }
+
+    #[test]
+    fn test_find_feature_for_path() {
+        let features = vec![
+            feature(
+                "src/features/a",
+                vec![feature("src/features/a/features/b", vec![])],
+            ),
+            feature("src/features/ab", vec![]),
+        ];
+
+        let find = |path: &str| find_feature_for_path(path, &features).map(|f| f.path.as_str());
+
+        assert_eq!(find("src/features/a/index.ts"), Some("src/features/a"));
+        assert_eq!(
+            find("./src/features/a/features/b/x.ts"),
+            Some("src/features/a/features/b")
+        );
+        assert_eq!(find("src/features/ab/x.ts"), Some("src/features/ab"));
+        assert_eq!(find("src/features/a"), Some("src/features/a"));
+        assert_eq!(find("src/other.ts"), None);
+    }
+}
@@ -6,10 +6,13 @@
 
 use anyhow::Result;
 use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
-use walkdir::WalkDir;
+
+use crate::cache::ScanCache;
+use crate::config::ScanSettings;
+use crate::walker::FileTree;
 
 /// Represents a single metadata entry's properties (key-value pairs)
 /// Uses IndexMap to preserve insertion order from comments
@@ -24,7 +27,7 @@ type MetadataByKey = HashMap<String, MetadataEntries>;
 /// Maps feature paths to their metadata, organized by metadata key
 pub type FeatureMetadataMap = HashMap<String, MetadataByKey>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureMetadataComment {
     #[allow(dead_code)]
     pub file_path: String,
@@ -173,8 +176,8 @@ fn check_line_for_feature_metadata(
     None
 }
 
-/// Scans a single file for feature metadata comments
-fn scan_file(file_path: &Path) -> Result<Vec<FeatureMetadataComment>> {
+/// Extracts the feature metadata comments of a file from its content
+pub fn extract_feature_metadata(file_path: &Path, content: &str) -> Vec<FeatureMetadataComment> {
     let mut results = Vec::new();
 
     // Get file extension
@@ -182,9 +185,6 @@ fn scan_file(file_path: &Path) -> Result<Vec<FeatureMetadataComment>> {
 
     let patterns = get_comment_patterns(extension);
 
-    // Read file content
-    let content = fs::read_to_string(file_path)?;
-
     // Check each line
     for (line_number, line) in content.lines().enumerate() {
         if let Some((metadata_key, properties)) = check_line_for_feature_metadata(line, &patterns) {
@@ -197,35 +197,31 @@ fn scan_file(file_path: &Path) -> Result<Vec<FeatureMetadataComment>> {
         }
     }
 
-    Ok(results)
+    results
 }
 
-/// Attempts to infer the feature path from a file path by looking for a 'features' directory
-/// in the path hierarchy and extracting the path from base to the feature directory
+/// Attempts to infer the feature path from a file path by looking for the outermost folder
+/// of the path hierarchy located in a container or matching a feature pattern
 ///
-/// For example (with base_path as project root):
+/// For example (with base_path as project root and the default `features` container):
 /// - `src/features/user-auth/component.tsx` -> Some("src/features/user-auth")
 /// - `libs/features/api-v2/utils.ts` -> Some("libs/features/api-v2")
 /// - `src/components/Button.tsx` -> None
-fn infer_feature_path_from_file(file_path: &Path, base_path: &Path) -> Option<String> {
+fn infer_feature_path_from_file(
+    file_path: &Path,
+    base_path: &Path,
+    settings: &ScanSettings,
+) -> Option<String> {
     // Get the relative path from base_path
     let relative_path = file_path.strip_prefix(base_path).ok()?;
 
-    // Look for 'features' directory in the path components
+    // Walk the parent directories of the file, from the base path down
+    let mut feature_path = std::path::PathBuf::new();
     let components: Vec<_> = relative_path.components().collect();
 
-    for (i, component) in components.iter().enumerate() {
-        if let Some(os_str) = component.as_os_str().to_str()
-            && os_str == "features"
-            && let Some(_next_component) = components.get(i + 1)
-        {
-            // Build the path up to and including the feature directory (i+1)
-            let mut feature_path = std::path::PathBuf::new();
-            for j in 0..=i + 1 {
-                if let Some(comp) = components.get(j) {
-                    feature_path.push(comp);
-                }
-            }
+    for component in components.iter().take(components.len().saturating_sub(1)) {
+        feature_path.push(component);
+        if settings.is_feature_location(&base_path.join(&feature_path), base_path) {
             return Some(feature_path.to_string_lossy().to_string());
         }
     }
@@ -239,62 +235,30 @@ fn infer_feature_path_from_file(file_path: &Path, base_path: &Path) -> Option<St
 /// - Outer key: feature name (from "feature:feature-1")
 /// - Inner key: metadata key (e.g., "flag" from "--feature-flag", "experiment" from "--feature-experiment")
 /// - Value: vector of property maps
-pub fn scan_directory_for_feature_metadata(dir_path: &Path) -> Result<FeatureMetadataMap> {
+pub fn scan_directory_for_feature_metadata(
+    tree: &FileTree,
+    settings: &ScanSettings,
+    cache: &ScanCache,
+) -> Result<FeatureMetadataMap> {
     let mut feature_metadata = FeatureMetadataMap::new();
 
-    // Skip common directories that shouldn't be scanned
-    let skip_dirs = [
-        "node_modules",
-        "target",
-        "dist",
-        "build",
-        ".git",
-        ".svn",
-        ".hg",
-        "vendor",
-        "__pycache__",
-        ".next",
-        ".nuxt",
-        "coverage",
-    ];
-
-    // Collect all entries first
-    let mut entries: Vec<_> = WalkDir::new(dir_path)
-        .into_iter()
-        .filter_entry(|e| {
-            if e.file_type().is_dir() {
-                let dir_name = e.file_name().to_string_lossy();
-                !skip_dirs.contains(&dir_name.as_ref())
-            } else {
-                true
-            }
-        })
-        .filter_map(|e| e.ok())
-        .collect();
-
-    // Sort entries by path for consistent cross-platform behavior
-    entries.sort_by(|a, b| a.path().cmp(b.path()));
-
-    for entry in entries {
-        if entry.file_type().is_file()
-            && let Ok(comments) = scan_file(entry.path())
-        {
-            for comment in comments {
-                // Get the feature path from the properties, or infer from file path
-                let feature_path = comment
-                    .properties
-                    .get("feature")
-                    .cloned()
-                    .or_else(|| infer_feature_path_from_file(entry.path(), dir_path));
-
-                if let Some(feature_path) = feature_path {
-                    feature_metadata
-                        .entry(feature_path)
-                        .or_default()
-                        .entry(comment.metadata_key)
-                        .or_default()
-                        .push(comment.properties);
-                }
+    // Files are sorted by path for consistent cross-platform behavior
+    for file_path in tree.files() {
+        for comment in cache.file_summary(file_path).metadata {
+            // Get the feature path from the properties, or infer from file path
+            let feature_path = comment
+                .properties
+                .get("feature")
+                .cloned()
+                .or_else(|| infer_feature_path_from_file(file_path, tree.root(), settings));
+
+            if let Some(feature_path) = feature_path {
+                feature_metadata
+                    .entry(feature_path)
+                    .or_default()
+                    .entry(comment.metadata_key)
+                    .or_default()
+                    .push(comment.properties);
             }
         }
     }
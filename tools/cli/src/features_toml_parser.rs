@@ -6,9 +6,10 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::collections::BTreeMap;
-use std::fs;
 use std::path::Path;
 
+use crate::walker::Source;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct FeaturesToml {
     pub name: Option<String>,
@@ -19,8 +20,9 @@ pub struct FeaturesToml {
 }
 
 /// Reads and parses a FEATURES.toml file
-pub fn read_features_toml(path: &Path) -> Result<FeaturesToml> {
-    let content = fs::read_to_string(path)
+pub fn read_features_toml(path: &Path, source: &Source) -> Result<FeaturesToml> {
+    let content = source
+        .read_to_string(path)
         .with_context(|| format!("could not read FEATURES.toml at `{}`", path.display()))?;
 
     let parsed: FeaturesToml = toml::from_str(&content)
@@ -29,19 +31,21 @@ pub fn read_features_toml(path: &Path) -> Result<FeaturesToml> {
     Ok(parsed)
 }
 
-/// Finds a FEATURES.toml file in a directory
-pub fn find_features_toml(dir_path: &Path) -> Option<std::path::PathBuf> {
-    let features_toml_path = dir_path.join("FEATURES.toml");
-    if features_toml_path.exists() {
-        Some(features_toml_path)
-    } else {
-        None
-    }
+/// File names of a feature manifest, in lookup order
+pub const FEATURES_TOML_NAMES: [&str; 2] = ["FEATURES.toml", "features.toml"];
+
+/// Finds a FEATURES.toml (or lowercase features.toml) file in a directory
+pub fn find_features_toml(dir_path: &Path, source: &Source) -> Option<std::path::PathBuf> {
+    FEATURES_TOML_NAMES
+        .iter()
+        .map(|name| dir_path.join(name))
+        .find(|path| source.is_file(path))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use std::io::Write;
     use tempfile::TempDir;
 
@@ -60,7 +64,7 @@ description = "A test feature"
         )
         .unwrap();
 
-        let result = read_features_toml(&toml_path).unwrap();
+        let result = read_features_toml(&toml_path, &Source::WorkingDirectory).unwrap();
         assert_eq!(result.name, Some("Test Feature".to_string()));
         assert_eq!(result.owner, Some("test-team".to_string()));
         assert_eq!(result.description, Some("A test feature".to_string()));
@@ -83,7 +87,7 @@ priority = "high"
         )
         .unwrap();
 
-        let result = read_features_toml(&toml_path).unwrap();
+        let result = read_features_toml(&toml_path, &Source::WorkingDirectory).unwrap();
         assert_eq!(result.name, Some("Feature with Meta".to_string()));
         assert_eq!(result.owner, Some("team-a".to_string()));
         assert!(result.meta.contains_key("status"));
@@ -96,15 +100,28 @@ priority = "high"
         let toml_path = temp_dir.path().join("FEATURES.toml");
         fs::File::create(&toml_path).unwrap();
 
-        let found = find_features_toml(temp_dir.path());
+        let found = find_features_toml(temp_dir.path(), &Source::WorkingDirectory);
         assert!(found.is_some());
         assert_eq!(found.unwrap(), toml_path);
     }
 
+    #[test]
+    fn test_find_lowercase_features_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let toml_path = temp_dir.path().join("features.toml");
+        fs::File::create(&toml_path).unwrap();
+
+        let found = find_features_toml(temp_dir.path(), &Source::WorkingDirectory);
+        assert_eq!(
+            found.unwrap().file_name().unwrap().to_ascii_lowercase(),
+            "features.toml"
+        );
+    }
+
     #[test]
     fn test_find_features_toml_not_found() {
         let temp_dir = TempDir::new().unwrap();
-        let found = find_features_toml(temp_dir.path());
+        let found = find_features_toml(temp_dir.path(), &Source::WorkingDirectory);
         assert!(found.is_none());
     }
 }
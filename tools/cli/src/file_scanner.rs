@@ -1,148 +1,262 @@
 use anyhow::{Context, Result};
-use git2::Repository;
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
-
+use indicatif::ProgressBar;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::cache::ScanCache;
+use crate::commit_links::{CommitLinks, normalize_name};
+use crate::config::ScanSettings;
+use crate::contributors::compute_contributors;
+use crate::coupling::add_couplings;
 use crate::dependency_resolver::{
     build_file_to_feature_map, collect_feature_info, resolve_feature_dependencies,
 };
+use crate::feature_detection::{
+    find_readme_file, is_documentation_directory, is_feature_directory,
+    is_inside_documentation_directory,
+};
 use crate::feature_metadata_detector::{self, FeatureMetadataMap};
 use crate::features_toml_parser::{find_features_toml, read_features_toml};
-use crate::git_helper::get_all_commits_by_path;
-use crate::import_detector::{ImportStatement, build_file_map, scan_file_for_imports};
+use crate::git_helper::{CommitHistory, read_commit_history};
+use crate::hotspots::{FileHotspot, top_hotspots};
+use crate::import_detector::{ImportStatement, build_file_map};
 use crate::models::{Change, Feature, Stats};
 use crate::readme_parser::read_readme_info;
-
-fn is_documentation_directory(dir_path: &Path) -> bool {
-    let dir_name = dir_path
-        .file_name()
-        .and_then(|name| name.to_str())
-        .unwrap_or("");
-
-    // Common documentation directory names
-    let doc_dirs = ["docs", "__docs__", ".docs"];
-
-    doc_dirs.contains(&dir_name.to_lowercase().as_str())
+use crate::walker::{FileTree, Source, discover_repository};
+
+/// State shared by every directory visited during a scan
+struct ScanContext<'a> {
+    base_path: &'a Path,
+    changes_map: Option<&'a HashMap<String, Vec<Change>>>,
+    /// Paths affected by each commit of the history, keyed by commit hash
+    commit_paths: &'a HashMap<&'a str, Vec<String>>,
+    /// Number of commits of each file, keyed by path relative to `base_path`
+    file_commits: &'a HashMap<String, usize>,
+    /// Commits naming features in their message
+    commit_links: &'a CommitLinks,
+    feature_metadata_map: &'a FeatureMetadataMap,
+    settings: &'a ScanSettings,
+    tree: &'a FileTree,
+    cache: &'a ScanCache<'a>,
+    /// Working directory of the repository the history was read from
+    repo_workdir: Option<PathBuf>,
 }
 
-fn is_inside_documentation_directory(dir_path: &Path) -> bool {
-    // Check if any parent directory is a documentation directory
-    for ancestor in dir_path.ancestors().skip(1) {
-        if is_documentation_directory(ancestor) {
-            return true;
-        }
-    }
-    false
+/// Scan the features of a directory, reporting the files read on `progress`
+///
+/// The directory is read as it is in `settings.revision` when set.
+pub fn list_files_recursive(
+    dir: &Path,
+    settings: &ScanSettings,
+    progress: &ProgressBar,
+) -> Result<Vec<Feature>> {
+    scan_tree(dir, false, settings, progress)
 }
 
-fn is_direct_subfolder_of_features(dir_path: &Path) -> bool {
-    if let Some(parent) = dir_path.parent()
-        && let Some(parent_name) = parent.file_name().and_then(|name| name.to_str())
-    {
-        return parent_name == "features";
-    }
-    false
+/// Scan the features of a directory with their git history
+pub fn list_files_recursive_with_changes(
+    dir: &Path,
+    settings: &ScanSettings,
+    progress: &ProgressBar,
+) -> Result<Vec<Feature>> {
+    scan_tree(dir, true, settings, progress)
 }
 
-fn find_readme_file(dir_path: &Path) -> Option<std::path::PathBuf> {
-    let readme_candidates = ["README.md", "README.mdx"];
+/// Scan the features of a directory with their git history, along with the churn of every
+/// file of the directory, files outside of features included
+pub fn list_files_recursive_with_hotspots(
+    dir: &Path,
+    settings: &ScanSettings,
+    progress: &ProgressBar,
+) -> Result<(Vec<Feature>, Vec<FileHotspot>)> {
+    with_scanned_tree(dir, true, settings, |tree, history, cache| {
+        let features = scan_tree_with_history(tree, history, settings, cache, progress)?;
+
+        let file_commits = history
+            .map(CommitHistory::commits_by_file)
+            .unwrap_or_default();
+        let files = tree
+            .files()
+            .filter_map(|path| {
+                let relative_path = path
+                    .strip_prefix(tree.root())
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .to_string();
+                let &commits = file_commits.get(&relative_path)?;
+                let lines = cache.file_summary(path).lines;
+                Some(FileHotspot::new(relative_path, commits, lines))
+            })
+            .collect();
 
-    for candidate in &readme_candidates {
-        let readme_path = dir_path.join(candidate);
-        if readme_path.exists() {
-            return Some(readme_path);
-        }
-    }
+        Ok((features, files))
+    })
+}
 
-    None
+fn scan_tree(
+    dir: &Path,
+    include_changes: bool,
+    settings: &ScanSettings,
+    progress: &ProgressBar,
+) -> Result<Vec<Feature>> {
+    with_scanned_tree(dir, include_changes, settings, |tree, history, cache| {
+        scan_tree_with_history(tree, history, settings, cache, progress)
+    })
 }
 
-/// Check if a directory has a README with `feature: true` in front matter
-fn has_feature_flag_in_readme(dir_path: &Path) -> bool {
-    if let Some(readme_path) = find_readme_file(dir_path)
-        && let Ok(content) = fs::read_to_string(&readme_path)
-    {
-        // Check if content starts with YAML front matter (---)
-        if let Some(stripped) = content.strip_prefix("---\n")
-            && let Some(end_pos) = stripped.find("\n---\n")
-        {
-            let yaml_content = &stripped[..end_pos];
+/// Walk a directory and read its history, then run `scan` on them and save the cache
+fn with_scanned_tree<T>(
+    dir: &Path,
+    include_changes: bool,
+    settings: &ScanSettings,
+    scan: impl FnOnce(&FileTree, Option<&CommitHistory>, &ScanCache) -> Result<T>,
+) -> Result<T> {
+    // Walk the directory once, every pass below reads from the same tree
+    let tree = match &settings.revision {
+        Some(revision) => FileTree::walk_revision(dir, revision, settings)?,
+        None => FileTree::walk(dir, settings)?,
+    };
 
-            // Parse YAML front matter
-            if let Ok(yaml_value) = serde_yaml::from_str::<serde_yaml::Value>(yaml_content)
-                && let Some(mapping) = yaml_value.as_mapping()
-            {
-                // Check for feature: true
-                if let Some(feature_value) =
-                    mapping.get(serde_yaml::Value::String("feature".to_string()))
-                {
-                    return feature_value.as_bool() == Some(true);
-                }
-            }
-        }
-    }
-    false
-}
+    // The saved cache describes the working directory only
+    let mut cache = if settings.cache && settings.revision.is_none() {
+        ScanCache::load(&tree)
+    } else {
+        ScanCache::in_memory(&tree)
+    };
 
-/// Check if a directory should be treated as a feature
-fn is_feature_directory(dir_path: &Path) -> bool {
-    // Skip documentation directories
-    if is_documentation_directory(dir_path) || is_inside_documentation_directory(dir_path) {
-        return false;
-    }
+    // Get all commits once at the beginning for efficiency
+    let history = if include_changes {
+        Some(
+            read_commit_history(
+                dir,
+                cache.take_history(),
+                tree.source().revision(),
+                &settings.history,
+                &feature_names(&tree, settings, &cache),
+            )
+            .unwrap_or_default(),
+        )
+    } else {
+        None
+    };
+
+    let scanned = scan(&tree, history.as_ref(), &cache)?;
 
-    // Check if it's a direct subfolder of "features" (existing behavior)
-    if is_direct_subfolder_of_features(dir_path) {
-        return true;
+    if let Some(history) = history {
+        cache.set_history(history);
     }
+    // A cache that cannot be written only makes the next scan slower
+    let _ = cache.save();
 
-    // Check if the directory has a README with feature: true
-    has_feature_flag_in_readme(dir_path)
+    Ok(scanned)
 }
 
-pub fn list_files_recursive(dir: &Path) -> Result<Vec<Feature>> {
-    // Scan entire base_path for feature metadata once
-    let feature_metadata =
-        feature_metadata_detector::scan_directory_for_feature_metadata(dir).unwrap_or_default();
+/// Normalized names, folder name and title, of every feature of a tree, which the commits
+/// outside of the scanned folder must name to be read with its history
+fn feature_names(tree: &FileTree, settings: &ScanSettings, cache: &ScanCache) -> BTreeSet<String> {
+    let source = tree.source();
+    tree.dirs()
+        .filter(|dir| {
+            dir.as_path() != tree.root() && is_feature_directory(dir, tree, settings, cache)
+        })
+        .flat_map(|dir| {
+            let folder_name = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string());
+            folder_name.into_iter().chain(feature_title(dir, source))
+        })
+        .map(|name| normalize_name(&name))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
 
-    // First pass: build feature structure without dependencies
-    let mut features = list_files_recursive_impl(dir, dir, None, None, &feature_metadata)?;
+/// Title of a feature, from its FEATURES.toml or else from its README
+fn feature_title(path: &Path, source: &Source) -> Option<String> {
+    match find_features_toml(path, source) {
+        Some(toml_path) => read_features_toml(&toml_path, source).ok()?.name,
+        None => {
+            read_readme_info(&find_readme_file(path, source)?, source)
+                .ok()?
+                .title
+        }
+    }
+}
 
-    // Second pass: scan for imports and resolve dependencies
-    populate_dependencies(&mut features, dir)?;
+fn scan_tree_with_history(
+    tree: &FileTree,
+    history: Option<&CommitHistory>,
+    settings: &ScanSettings,
+    cache: &ScanCache,
+    progress: &ProgressBar,
+) -> Result<Vec<Feature>> {
+    let dir = tree.root();
 
-    Ok(features)
-}
+    // Read every file once, in parallel, for the passes below
+    let files: Vec<&Path> = tree.files().map(PathBuf::as_path).collect();
+    cache.summarize_all(&files, progress);
 
-pub fn list_files_recursive_with_changes(dir: &Path) -> Result<Vec<Feature>> {
-    // Get all commits once at the beginning for efficiency
-    let all_commits = get_all_commits_by_path(dir).unwrap_or_default();
     // Scan entire base_path for feature metadata once
     let feature_metadata =
-        feature_metadata_detector::scan_directory_for_feature_metadata(dir).unwrap_or_default();
+        feature_metadata_detector::scan_directory_for_feature_metadata(tree, settings, cache)
+            .unwrap_or_default();
+
+    let changes_map = history.map(CommitHistory::changes_by_path);
+    let commit_paths = history
+        .map(CommitHistory::paths_by_commit)
+        .unwrap_or_default();
+    let file_commits = history
+        .map(CommitHistory::commits_by_file)
+        .unwrap_or_default();
+    let commit_links = history.map(CommitLinks::from_history).unwrap_or_default();
+
+    let ctx = ScanContext {
+        base_path: dir,
+        changes_map: changes_map.as_ref(),
+        commit_paths: &commit_paths,
+        file_commits: &file_commits,
+        commit_links: &commit_links,
+        feature_metadata_map: &feature_metadata,
+        settings,
+        tree,
+        cache,
+        repo_workdir: discover_repository(dir)
+            .ok()
+            .and_then(|(repo, _)| repo.workdir().map(Path::to_path_buf)),
+    };
 
     // First pass: build feature structure without dependencies
-    let mut features =
-        list_files_recursive_impl(dir, dir, Some(&all_commits), None, &feature_metadata)?;
+    let mut features = list_files_recursive_impl(dir, None, &ctx)?;
 
     // Second pass: scan for imports and resolve dependencies
-    populate_dependencies(&mut features, dir)?;
+    populate_dependencies(&mut features, tree, settings, cache)?;
+
+    // Third pass: couple the features changed in the same commits
+    if let Some(history) = history {
+        add_couplings(&mut features, &commit_paths, &history.subtree);
+    }
 
     Ok(features)
 }
 
 /// Populate dependencies for all features by scanning imports
-fn populate_dependencies(features: &mut [Feature], base_path: &Path) -> Result<()> {
+fn populate_dependencies(
+    features: &mut [Feature],
+    tree: &FileTree,
+    settings: &ScanSettings,
+    cache: &ScanCache,
+) -> Result<()> {
+    let base_path = tree.root();
+
     // Build file map for quick path resolution
-    let file_map = build_file_map(base_path);
+    let file_map = build_file_map(tree);
 
     // Collect all feature info (flat list with paths)
     let mut feature_info_list = Vec::new();
     collect_feature_info(features, None, &mut feature_info_list);
 
     // Build file-to-feature mapping
-    let file_to_feature_map = build_file_to_feature_map(&feature_info_list, base_path);
+    let file_to_feature_map =
+        build_file_to_feature_map(&feature_info_list, base_path, tree, settings, cache);
 
     // Build feature path to name mapping (path is the unique identifier)
     let mut feature_path_to_name_map = HashMap::new();
@@ -155,7 +269,7 @@ fn populate_dependencies(features: &mut [Feature], base_path: &Path) -> Result<(
 
     for feature_info in &feature_info_list {
         let feature_path = base_path.join(&feature_info.path);
-        let imports = scan_feature_directory_for_imports(&feature_path);
+        let imports = scan_feature_directory_for_imports(&feature_path, tree, settings, cache);
         // Use feature path as key instead of name to handle features with duplicate names
         feature_imports.insert(feature_info.path.to_string_lossy().to_string(), imports);
     }
@@ -163,7 +277,7 @@ fn populate_dependencies(features: &mut [Feature], base_path: &Path) -> Result<(
     // Now populate dependencies in the feature tree
     populate_dependencies_recursive(
         features,
-        base_path,
+        tree,
         &feature_imports,
         &file_to_feature_map,
         &feature_path_to_name_map,
@@ -174,44 +288,38 @@ fn populate_dependencies(features: &mut [Feature], base_path: &Path) -> Result<(
 }
 
 /// Scan a feature directory for all import statements
-fn scan_feature_directory_for_imports(feature_path: &Path) -> Vec<ImportStatement> {
+fn scan_feature_directory_for_imports(
+    feature_path: &Path,
+    tree: &FileTree,
+    settings: &ScanSettings,
+    cache: &ScanCache,
+) -> Vec<ImportStatement> {
     let mut all_imports = Vec::new();
 
-    if let Ok(entries) = fs::read_dir(feature_path) {
-        // Collect and sort entries alphabetically by filename
-        let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
-        paths.sort_by(|a, b| {
-            let name_a = a.file_name().unwrap_or_default().to_string_lossy();
-            let name_b = b.file_name().unwrap_or_default().to_string_lossy();
-            name_a.cmp(&name_b)
-        });
-
-        for path in paths {
-            // Skip documentation directories
-            if is_documentation_directory(&path) {
+    // Entries are already sorted alphabetically by filename
+    for path in tree.read_dir(feature_path) {
+        // Skip documentation directories
+        if is_documentation_directory(path, settings) {
+            continue;
+        }
+
+        if tree.is_file(path) {
+            all_imports.extend(cache.file_summary(path).imports);
+        } else if tree.is_dir(path) {
+            // Skip container subdirectories (contain nested features)
+            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if settings.is_container(dir_name) {
                 continue;
             }
 
-            if path.is_file() {
-                if let Ok(imports) = scan_file_for_imports(&path) {
-                    all_imports.extend(imports);
-                }
-            } else if path.is_dir() {
-                // Skip 'features' subdirectory (contains nested features)
-                let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if dir_name == "features" {
-                    continue;
-                }
-
-                // Skip nested feature directories (check if it's a direct child of "features" directory with README)
-                if is_feature_directory(&path) {
-                    continue;
-                }
-
-                // Recursively scan subdirectories
-                let nested_imports = scan_feature_directory_for_imports(&path);
-                all_imports.extend(nested_imports);
+            // Skip nested feature directories
+            if is_feature_directory(path, tree, settings, cache) {
+                continue;
             }
+
+            // Recursively scan subdirectories
+            let nested_imports = scan_feature_directory_for_imports(path, tree, settings, cache);
+            all_imports.extend(nested_imports);
         }
     }
 
@@ -221,7 +329,7 @@ fn scan_feature_directory_for_imports(feature_path: &Path) -> Vec<ImportStatemen
 /// Recursively populate dependencies in the feature tree
 fn populate_dependencies_recursive(
     features: &mut [Feature],
-    base_path: &Path,
+    tree: &FileTree,
     feature_imports: &HashMap<String, Vec<ImportStatement>>,
     file_to_feature_map: &HashMap<std::path::PathBuf, String>,
     feature_path_to_name_map: &HashMap<String, String>,
@@ -236,7 +344,7 @@ fn populate_dependencies_recursive(
             let dependencies = resolve_feature_dependencies(
                 &feature.name,
                 &feature_path,
-                base_path,
+                tree,
                 imports,
                 file_to_feature_map,
                 feature_path_to_name_map,
@@ -250,7 +358,7 @@ fn populate_dependencies_recursive(
         if !feature.features.is_empty() {
             populate_dependencies_recursive(
                 &mut feature.features,
-                base_path,
+                tree,
                 feature_imports,
                 file_to_feature_map,
                 feature_path_to_name_map,
@@ -260,7 +368,7 @@ fn populate_dependencies_recursive(
     }
 }
 
-fn read_decision_files(feature_path: &Path) -> Result<Vec<String>> {
+fn read_decision_files(feature_path: &Path, source: &Source) -> Result<Vec<String>> {
     let mut decisions = Vec::new();
 
     // Check both "decision" and "decisions" folder names
@@ -270,8 +378,8 @@ fn read_decision_files(feature_path: &Path) -> Result<Vec<String>> {
     ];
 
     for decisions_dir in &decision_paths {
-        if decisions_dir.exists() && decisions_dir.is_dir() {
-            let entries = fs::read_dir(decisions_dir).with_context(|| {
+        if source.exists(decisions_dir) && source.is_dir(decisions_dir) {
+            let entries = source.read_dir(decisions_dir).with_context(|| {
                 format!(
                     "could not read decisions directory `{}`",
                     decisions_dir.display()
@@ -281,12 +389,9 @@ fn read_decision_files(feature_path: &Path) -> Result<Vec<String>> {
             // Collect all decision file paths first
             let mut decision_paths_vec = Vec::new();
 
-            for entry in entries {
-                let entry = entry?;
-                let path = entry.path();
-
+            for path in entries {
                 // Skip README.md files and only process .md files
-                if path.is_file()
+                if source.is_file(&path)
                     && let Some(file_name) = path.file_name()
                 {
                     let file_name_str = file_name.to_string_lossy();
@@ -305,7 +410,7 @@ fn read_decision_files(feature_path: &Path) -> Result<Vec<String>> {
 
             // Read the sorted files
             for path in decision_paths_vec {
-                let content = fs::read_to_string(&path).with_context(|| {
+                let content = source.read_to_string(&path).with_context(|| {
                     format!("could not read decision file `{}`", path.display())
                 })?;
                 decisions.push(content);
@@ -318,254 +423,87 @@ fn read_decision_files(feature_path: &Path) -> Result<Vec<String>> {
     Ok(decisions)
 }
 
-/// Count the number of files in a feature directory (excluding documentation)
-fn count_files(feature_path: &Path, nested_feature_paths: &[String]) -> usize {
-    let mut file_count = 0;
-
-    if let Ok(entries) = fs::read_dir(feature_path) {
-        // Collect and sort entries alphabetically by filename
-        let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
-        paths.sort_by(|a, b| {
-            let name_a = a.file_name().unwrap_or_default().to_string_lossy();
-            let name_b = b.file_name().unwrap_or_default().to_string_lossy();
-            name_a.cmp(&name_b)
-        });
-
-        for path in paths {
-            let path_str = path.to_string_lossy().to_string();
-
-            // Skip documentation directories
-            if is_documentation_directory(&path) {
-                continue;
-            }
-
-            // Skip nested feature directories
-            if nested_feature_paths
-                .iter()
-                .any(|nfp| path_str.starts_with(nfp))
-            {
-                continue;
-            }
-
-            if path.is_file() {
-                file_count += 1;
-            } else if path.is_dir() {
-                // Recursively count files in subdirectories
-                file_count += count_files(&path, nested_feature_paths);
-            }
-        }
-    }
-
-    file_count
-}
-
-/// Count the total number of lines in all files in a feature directory (excluding documentation)
-fn count_lines(feature_path: &Path, nested_feature_paths: &[String]) -> usize {
-    let mut line_count = 0;
-
-    if let Ok(entries) = fs::read_dir(feature_path) {
-        // Collect and sort entries alphabetically by filename
-        let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
-        paths.sort_by(|a, b| {
-            let name_a = a.file_name().unwrap_or_default().to_string_lossy();
-            let name_b = b.file_name().unwrap_or_default().to_string_lossy();
-            name_a.cmp(&name_b)
-        });
-
-        for path in paths {
-            let path_str = path.to_string_lossy().to_string();
-
-            // Skip documentation directories
-            if is_documentation_directory(&path) {
-                continue;
-            }
-
-            // Skip nested feature directories
-            if nested_feature_paths
-                .iter()
-                .any(|nfp| path_str.starts_with(nfp))
-            {
-                continue;
-            }
-
-            if path.is_file() {
-                // Try to read the file and count lines
-                if let Ok(content) = fs::read_to_string(&path) {
-                    line_count += content.lines().count();
-                }
-            } else if path.is_dir() {
-                // Recursively count lines in subdirectories
-                line_count += count_lines(&path, nested_feature_paths);
-            }
-        }
-    }
-
-    line_count
+/// Number of files, lines and TODO comments of a feature, with the churn of its files
+#[derive(Default)]
+struct FileCounts {
+    files: usize,
+    lines: usize,
+    todos: usize,
+    hotspots: Vec<FileHotspot>,
 }
 
-/// Count the total number of TODO comments in all files in a feature directory (excluding documentation)
-fn count_todos(feature_path: &Path, nested_feature_paths: &[String]) -> usize {
-    let mut todo_count = 0;
-
-    if let Ok(entries) = fs::read_dir(feature_path) {
-        // Collect and sort entries alphabetically by filename
-        let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
-        paths.sort_by(|a, b| {
-            let name_a = a.file_name().unwrap_or_default().to_string_lossy();
-            let name_b = b.file_name().unwrap_or_default().to_string_lossy();
-            name_a.cmp(&name_b)
-        });
-
-        for path in paths {
-            let path_str = path.to_string_lossy().to_string();
-
-            // Skip documentation directories
-            if is_documentation_directory(&path) {
-                continue;
-            }
-
-            // Skip nested feature directories
-            if nested_feature_paths
-                .iter()
-                .any(|nfp| path_str.starts_with(nfp))
-            {
-                continue;
-            }
+/// Count the files, lines and TODO comments of a feature directory (excluding documentation
+/// and nested features)
+fn count_files(
+    feature_path: &Path,
+    nested_feature_paths: &[String],
+    ctx: &ScanContext,
+    counts: &mut FileCounts,
+) {
+    // Entries are already sorted alphabetically by filename
+    for path in ctx.tree.read_dir(feature_path) {
+        let path_str = path.to_string_lossy().to_string();
 
-            if path.is_file() {
-                // Try to read the file and count TODO comments
-                if let Ok(content) = fs::read_to_string(&path) {
-                    for line in content.lines() {
-                        // Look for TODO in comments (case-insensitive)
-                        let line_upper = line.to_uppercase();
-                        if line_upper.contains("TODO") {
-                            todo_count += 1;
-                        }
-                    }
-                }
-            } else if path.is_dir() {
-                // Recursively count TODOs in subdirectories
-                todo_count += count_todos(&path, nested_feature_paths);
-            }
+        // Skip documentation directories
+        if is_documentation_directory(path, ctx.settings) {
+            continue;
         }
-    }
-
-    todo_count
-}
 
-/// Get the paths affected by a specific commit
-fn get_commit_affected_paths(repo: &Repository, commit_hash: &str) -> Vec<String> {
-    let Ok(oid) = git2::Oid::from_str(commit_hash) else {
-        return Vec::new();
-    };
-
-    let Ok(commit) = repo.find_commit(oid) else {
-        return Vec::new();
-    };
-
-    let mut paths = Vec::new();
-
-    // For the first commit (no parents), get all files in the tree
-    if commit.parent_count() == 0 {
-        if let Ok(tree) = commit.tree() {
-            collect_all_tree_paths(repo, &tree, "", &mut paths);
+        // Skip nested feature directories
+        if nested_feature_paths
+            .iter()
+            .any(|nfp| path_str.starts_with(nfp))
+        {
+            continue;
         }
-        return paths;
-    }
-
-    // For commits with parents, check the diff
-    let Ok(tree) = commit.tree() else {
-        return Vec::new();
-    };
 
-    let Ok(parent) = commit.parent(0) else {
-        return Vec::new();
-    };
-
-    let Ok(parent_tree) = parent.tree() else {
-        return Vec::new();
-    };
-
-    if let Ok(diff) = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None) {
-        let _ = diff.foreach(
-            &mut |delta, _| {
-                if let Some(path) = delta.new_file().path()
-                    && let Some(path_str) = path.to_str()
-                {
-                    paths.push(path_str.to_string());
-                }
-                if let Some(path) = delta.old_file().path()
-                    && let Some(path_str) = path.to_str()
-                    && !paths.contains(&path_str.to_string())
-                {
-                    paths.push(path_str.to_string());
-                }
-                true
-            },
-            None,
-            None,
-            None,
-        );
-    }
-
-    paths
-}
-
-/// Collect all file paths in a tree (helper for get_commit_affected_paths)
-fn collect_all_tree_paths(
-    repo: &Repository,
-    tree: &git2::Tree,
-    prefix: &str,
-    paths: &mut Vec<String>,
-) {
-    for entry in tree.iter() {
-        if let Some(name) = entry.name() {
-            let path = if prefix.is_empty() {
-                name.to_string()
-            } else {
-                format!("{}/{}", prefix, name)
-            };
-
-            paths.push(path.clone());
-
-            if entry.kind() == Some(git2::ObjectType::Tree)
-                && let Ok(obj) = entry.to_object(repo)
-                && let Ok(subtree) = obj.peel_to_tree()
-            {
-                collect_all_tree_paths(repo, &subtree, &path, paths);
+        if ctx.tree.is_file(path) {
+            let summary = ctx.cache.file_summary(path);
+            counts.files += 1;
+            counts.lines += summary.lines;
+            counts.todos += summary.todos;
+
+            let relative_path = path
+                .strip_prefix(ctx.base_path)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string();
+            // Files of nested features are hotspots of their own feature only
+            let in_nested = nested_feature_paths
+                .iter()
+                .any(|nested| Path::new(&relative_path).starts_with(nested));
+            if !in_nested && let Some(&commits) = ctx.file_commits.get(&relative_path) {
+                counts
+                    .hotspots
+                    .push(FileHotspot::new(relative_path, commits, summary.lines));
             }
+        } else if ctx.tree.is_dir(path) {
+            // Recursively count files in subdirectories
+            count_files(path, nested_feature_paths, ctx, counts);
         }
     }
 }
 
-/// Compute statistics from changes for a feature
-fn compute_stats_from_changes(
-    changes: &[Change],
+/// Flag the changes of a feature only affecting files of its nested features
+fn mark_nested_only_changes(
+    changes: &mut [Change],
     feature_path: &Path,
     nested_features: &[Feature],
-) -> Option<Stats> {
-    if changes.is_empty() {
-        return None;
-    }
+    ctx: &ScanContext,
+) {
+    let source = ctx.tree.source();
 
     // Collect paths of nested features to exclude from commit counts
     let nested_feature_paths: Vec<String> =
         nested_features.iter().map(|f| f.path.clone()).collect();
 
-    // Get repository to check commit details
-    let repo = Repository::discover(feature_path).ok();
-
     // Get the feature's relative path from repo root
-    let feature_relative_path = if let Some(ref r) = repo {
-        if let Ok(canonical_path) = std::fs::canonicalize(feature_path) {
-            if let Some(workdir) = r.workdir() {
-                canonical_path
-                    .strip_prefix(workdir)
-                    .ok()
-                    .map(|p| p.to_string_lossy().to_string())
-            } else {
-                None
-            }
+    let feature_relative_path = if let Some(ref workdir) = ctx.repo_workdir {
+        if let Ok(canonical_path) = source.canonicalize(feature_path) {
+            canonical_path
+                .strip_prefix(workdir)
+                .ok()
+                .map(|p| p.to_string_lossy().to_string())
         } else {
             None
         }
@@ -573,47 +511,57 @@ fn compute_stats_from_changes(
         None
     };
 
-    // Filter changes to only include those that affect files in this feature
-    // (not exclusively in nested features)
-    let filtered_changes: Vec<&Change> = changes
-        .iter()
-        .filter(|change| {
-            // If we don't have repo access, include all changes
-            let Some(ref r) = repo else {
-                return true;
-            };
+    // Own changes affect files in this feature (not exclusively in nested features)
+    let is_own_change = |change: &Change| {
+        // Commits naming the feature are its own whatever files they touch
+        if change.linked_by_message {
+            return true;
+        }
 
-            let Some(ref feature_rel_path) = feature_relative_path else {
-                return true;
-            };
+        // If we don't have repo access, include all changes
+        let Some(ref workdir) = ctx.repo_workdir else {
+            return true;
+        };
 
-            // Get the files affected by this commit
-            let affected_files = get_commit_affected_paths(r, &change.hash);
-
-            // Check if any affected file is in this feature but not in a nested feature
-            affected_files.iter().any(|file_path| {
-                // File must be in this feature
-                let in_feature = file_path.starts_with(feature_rel_path);
-
-                // File must not be exclusively in a nested feature
-                let in_nested = nested_feature_paths.iter().any(|nested_path| {
-                    // Convert nested absolute path to relative path
-                    if let Ok(nested_canonical) = std::fs::canonicalize(nested_path)
-                        && let Some(workdir) = r.workdir()
-                        && let Ok(nested_rel) = nested_canonical.strip_prefix(workdir)
-                    {
-                        let nested_rel_str = nested_rel.to_string_lossy();
-                        return file_path.starts_with(nested_rel_str.as_ref());
-                    }
-                    false
-                });
+        let Some(ref feature_rel_path) = feature_relative_path else {
+            return true;
+        };
 
-                in_feature && !in_nested
-            })
+        // Get the files affected by this commit
+        let affected_files = ctx
+            .commit_paths
+            .get(change.hash.as_str())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        // Check if any affected file is in this feature but not in a nested feature
+        affected_files.iter().any(|file_path| {
+            // File must be in this feature
+            let in_feature = Path::new(file_path).starts_with(feature_rel_path);
+
+            // File must not be exclusively in a nested feature
+            let in_nested = nested_feature_paths.iter().any(|nested_path| {
+                // Nested feature paths are relative to the scanned folder
+                if let Ok(nested_canonical) = source.canonicalize(&ctx.base_path.join(nested_path))
+                    && let Ok(nested_rel) = nested_canonical.strip_prefix(workdir)
+                {
+                    return Path::new(file_path).starts_with(nested_rel);
+                }
+                false
+            });
+
+            in_feature && !in_nested
         })
-        .collect();
+    };
+
+    for change in changes {
+        change.nested_only = !is_own_change(change);
+    }
+}
 
-    let mut commits = std::collections::BTreeMap::new();
+/// Compute commit statistics from the own changes of a feature
+fn compute_commit_stats(filtered_changes: &[&Change]) -> BTreeMap<String, serde_json::Value> {
+    let mut commits = BTreeMap::new();
 
     // Add total commit count
     commits.insert(
@@ -623,7 +571,7 @@ fn compute_stats_from_changes(
 
     // Count commits by author
     let mut authors_count: HashMap<String, usize> = HashMap::new();
-    for change in &filtered_changes {
+    for change in filtered_changes {
         *authors_count.entry(change.author_name.clone()).or_insert(0) += 1;
     }
     commits.insert(
@@ -633,7 +581,7 @@ fn compute_stats_from_changes(
 
     // Count commits by conventional commit type
     let mut count_by_type: HashMap<String, usize> = HashMap::new();
-    for change in &filtered_changes {
+    for change in filtered_changes {
         let commit_type = extract_commit_type(&change.title);
         *count_by_type.entry(commit_type).or_insert(0) += 1;
     }
@@ -656,22 +604,11 @@ fn compute_stats_from_changes(
         );
     }
 
-    // Count files and lines in the feature directory (excluding nested features)
-    let files_count = count_files(feature_path, &nested_feature_paths);
-    let lines_count = count_lines(feature_path, &nested_feature_paths);
-    let todos_count = count_todos(feature_path, &nested_feature_paths);
-
-    Some(Stats {
-        files_count: Some(files_count),
-        lines_count: Some(lines_count),
-        todos_count: Some(todos_count),
-        commits,
-        coverage: None,
-    })
+    commits
 }
 
 /// Extract the commit type from a conventional commit title
-fn extract_commit_type(title: &str) -> String {
+pub fn extract_commit_type(title: &str) -> String {
     // Common conventional commit types
     let known_types = [
         "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore",
@@ -689,7 +626,8 @@ fn extract_commit_type(title: &str) -> String {
             prefix
         };
 
-        let type_part = type_part.trim().to_lowercase();
+        // Remove the breaking change marker (e.g., "feat!" -> "feat")
+        let type_part = type_part.trim().trim_end_matches('!').to_lowercase();
 
         // Check if it's a known conventional commit type
         if known_types.contains(&type_part.as_str()) {
@@ -703,62 +641,63 @@ fn extract_commit_type(title: &str) -> String {
 
 fn process_feature_directory(
     path: &Path,
-    base_path: &Path,
     name: &str,
-    changes_map: Option<&HashMap<String, Vec<Change>>>,
     parent_owner: Option<&str>,
-    feature_metadata_map: &FeatureMetadataMap,
+    ctx: &ScanContext,
 ) -> Result<Feature> {
+    let source = ctx.tree.source();
+
     // First try to find and read FEATURES.toml file
-    let (title, owner, description, mut meta) = if let Some(toml_path) = find_features_toml(path) {
-        if let Ok(toml_data) = read_features_toml(&toml_path) {
-            (
-                toml_data.name,
-                toml_data.owner.unwrap_or_default(),
-                toml_data.description.unwrap_or_default(),
-                toml_data.meta,
-            )
+    let (title, owner, description, mut meta) =
+        if let Some(toml_path) = find_features_toml(path, source) {
+            if let Ok(toml_data) = read_features_toml(&toml_path, source) {
+                (
+                    toml_data.name,
+                    toml_data.owner.unwrap_or_default(),
+                    toml_data.description.unwrap_or_default(),
+                    toml_data.meta,
+                )
+            } else {
+                (
+                    None,
+                    String::new(),
+                    String::new(),
+                    std::collections::BTreeMap::new(),
+                )
+            }
         } else {
+            // Fall back to README file if FEATURES.toml not found
+            let readme_info = if let Some(readme_path) = find_readme_file(path, source) {
+                read_readme_info(&readme_path, source)?
+            } else {
+                use crate::readme_parser::ReadmeInfo;
+                ReadmeInfo {
+                    title: None,
+                    owner: "".to_string(),
+                    description: "".to_string(),
+                    meta: std::collections::BTreeMap::new(),
+                }
+            };
             (
-                None,
-                String::new(),
-                String::new(),
-                std::collections::BTreeMap::new(),
+                readme_info.title,
+                readme_info.owner,
+                readme_info.description,
+                readme_info.meta,
             )
-        }
-    } else {
-        // Fall back to README file if FEATURES.toml not found
-        let readme_info = if let Some(readme_path) = find_readme_file(path) {
-            read_readme_info(&readme_path)?
-        } else {
-            use crate::readme_parser::ReadmeInfo;
-            ReadmeInfo {
-                title: None,
-                owner: "".to_string(),
-                description: "".to_string(),
-                meta: std::collections::BTreeMap::new(),
-            }
         };
-        (
-            readme_info.title,
-            readme_info.owner,
-            readme_info.description,
-            readme_info.meta,
-        )
-    };
 
     // Remove the 'feature' key from meta if it exists (it's redundant since we know it's a feature)
     meta.remove("feature");
 
     // Get the relative path to this feature directory for metadata lookup
     let relative_path = path
-        .strip_prefix(base_path)
+        .strip_prefix(ctx.base_path)
         .unwrap_or(path)
         .to_string_lossy()
         .to_string();
 
     // Check if this feature has any metadata from the global scan (matched by feature path)
-    if let Some(metadata_map) = feature_metadata_map.get(&relative_path) {
+    if let Some(metadata_map) = ctx.feature_metadata_map.get(&relative_path) {
         // Iterate through each metadata key (e.g., "feature-flag", "feature-experiment")
         for (metadata_key, flags) in metadata_map {
             // Convert Vec<HashMap<String, String>> to JSON array
@@ -784,15 +723,18 @@ fn process_feature_directory(
         }
     }
 
-    let changes = if let Some(map) = changes_map {
+    let mut changes = if let Some(map) = ctx.changes_map {
         // Convert the absolute path to a repo-relative path and look up changes
-        get_changes_for_path(path, map).unwrap_or_default()
+        get_changes_for_path(path, map, ctx).unwrap_or_default()
     } else {
         Vec::new()
     };
+    // Commits naming the feature by its title or folder name belong to it too
+    let feature_names: Vec<&str> = title.as_deref().into_iter().chain([name]).collect();
+    ctx.commit_links.link_changes(&mut changes, &feature_names);
 
     // Always include decisions regardless of include_changes flag
-    let decisions = read_decision_files(path).unwrap_or_default();
+    let decisions = read_decision_files(path, source).unwrap_or_default();
 
     // Determine the actual owner and whether it's inherited
     let (actual_owner, is_owner_inherited) = if owner.is_empty() {
@@ -805,57 +747,36 @@ fn process_feature_directory(
         (owner.clone(), false)
     };
 
-    // Check if this feature has nested features in a 'features' subdirectory
-    let nested_features_path = path.join("features");
-    let mut nested_features = if nested_features_path.exists() && nested_features_path.is_dir() {
-        list_files_recursive_impl(
-            &nested_features_path,
-            base_path,
-            changes_map,
-            Some(&actual_owner),
-            feature_metadata_map,
-        )
-        .unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-
-    // Also check for nested features marked with feature: true in subdirectories
-    let entries = fs::read_dir(path)
-        .with_context(|| format!("could not read directory `{}`", path.display()))?;
-
-    let mut entries: Vec<_> = entries.collect::<Result<_, _>>()?;
-    entries.sort_by_key(|entry| entry.path());
+    // Check if this feature has nested features in container subdirectories
+    let mut nested_features = Vec::new();
+    for container in &ctx.settings.containers {
+        let nested_features_path = path.join(container);
+        if ctx.tree.is_dir(&nested_features_path) {
+            nested_features.extend(
+                list_files_recursive_impl(&nested_features_path, Some(&actual_owner), ctx)
+                    .unwrap_or_default(),
+            );
+        }
+    }
 
-    for entry in entries {
-        let entry_path = entry.path();
+    // Also check for nested features marked with feature: true or matching a pattern in subdirectories
+    for entry_path in ctx.tree.read_dir(path) {
         let entry_name = entry_path.file_name().unwrap().to_string_lossy();
 
-        if entry_path.is_dir()
-            && entry_name != "features" // Don't process 'features' folder twice
-            && !is_documentation_directory(&entry_path)
+        if ctx.tree.is_dir(entry_path)
+            && !ctx.settings.is_container(&entry_name) // Don't process containers twice
+            && !is_documentation_directory(entry_path, ctx.settings)
         {
-            if has_feature_flag_in_readme(&entry_path) {
+            if is_feature_directory(entry_path, ctx.tree, ctx.settings, ctx.cache) {
                 // This directory is a feature itself
-                let nested_feature = process_feature_directory(
-                    &entry_path,
-                    base_path,
-                    &entry_name,
-                    changes_map,
-                    Some(&actual_owner),
-                    feature_metadata_map,
-                )?;
+                let nested_feature =
+                    process_feature_directory(entry_path, &entry_name, Some(&actual_owner), ctx)?;
                 nested_features.push(nested_feature);
             } else {
                 // This directory is not a feature, but might contain features
                 // Recursively search for features inside it
-                let deeper_features = list_files_recursive_impl(
-                    &entry_path,
-                    base_path,
-                    changes_map,
-                    Some(&actual_owner),
-                    feature_metadata_map,
-                )?;
+                let deeper_features =
+                    list_files_recursive_impl(entry_path, Some(&actual_owner), ctx)?;
                 nested_features.extend(deeper_features);
             }
         }
@@ -865,30 +786,33 @@ fn process_feature_directory(
     let nested_feature_paths: Vec<String> =
         nested_features.iter().map(|f| f.path.clone()).collect();
 
-    // Always compute file, line, and TODO counts
-    let files_count = count_files(path, &nested_feature_paths);
-    let lines_count = count_lines(path, &nested_feature_paths);
-    let todos_count = count_todos(path, &nested_feature_paths);
+    // Always compute file, line, and TODO counts, commit stats only when there are changes
+    let mut counts = FileCounts::default();
+    count_files(path, &nested_feature_paths, ctx, &mut counts);
 
-    // Compute stats from changes if available, otherwise create basic stats
-    let stats =
-        if let Some(change_stats) = compute_stats_from_changes(&changes, path, &nested_features) {
-            // If we have change stats, they already include files/lines/todos counts
-            Some(change_stats)
+    mark_nested_only_changes(&mut changes, path, &nested_features, ctx);
+    let feature_own_changes: Vec<&Change> = changes
+        .iter()
+        .filter(|change| !change.nested_only)
+        .collect();
+    let stats = Some(Stats {
+        files_count: Some(counts.files),
+        lines_count: Some(counts.lines),
+        todos_count: Some(counts.todos),
+        commits: if changes.is_empty() {
+            BTreeMap::new()
         } else {
-            // No changes, but we still want to include file/line/todo counts
-            Some(Stats {
-                files_count: Some(files_count),
-                lines_count: Some(lines_count),
-                todos_count: Some(todos_count),
-                commits: std::collections::BTreeMap::new(),
-                coverage: None,
-            })
-        };
+            compute_commit_stats(&feature_own_changes)
+        },
+        contributors: compute_contributors(&feature_own_changes),
+        coupling: Vec::new(),
+        hotspots: top_hotspots(counts.hotspots),
+        coverage: None,
+    });
 
     // Make path relative to base_path
     let relative_path = path
-        .strip_prefix(base_path)
+        .strip_prefix(ctx.base_path)
         .unwrap_or(path)
         .to_string_lossy()
         .to_string();
@@ -910,45 +834,23 @@ fn process_feature_directory(
 
 fn list_files_recursive_impl(
     dir: &Path,
-    base_path: &Path,
-    changes_map: Option<&HashMap<String, Vec<Change>>>,
     parent_owner: Option<&str>,
-    feature_metadata_map: &FeatureMetadataMap,
+    ctx: &ScanContext,
 ) -> Result<Vec<Feature>> {
-    let entries = fs::read_dir(dir)
-        .with_context(|| format!("could not read directory `{}`", dir.display()))?;
-
-    let mut entries: Vec<_> = entries.collect::<Result<_, _>>()?;
-    entries.sort_by_key(|entry| entry.path());
-
     let mut features: Vec<Feature> = Vec::new();
 
-    for entry in entries {
-        let path = entry.path();
+    for path in ctx.tree.read_dir(dir) {
         let name = path.file_name().unwrap().to_string_lossy();
 
-        if path.is_dir() {
-            if is_feature_directory(&path) {
-                let feature = process_feature_directory(
-                    &path,
-                    base_path,
-                    &name,
-                    changes_map,
-                    parent_owner,
-                    feature_metadata_map,
-                )?;
+        if ctx.tree.is_dir(path) {
+            if is_feature_directory(path, ctx.tree, ctx.settings, ctx.cache) {
+                let feature = process_feature_directory(path, &name, parent_owner, ctx)?;
                 features.push(feature);
-            } else if !is_documentation_directory(&path)
-                && !is_inside_documentation_directory(&path)
+            } else if !is_documentation_directory(path, ctx.settings)
+                && !is_inside_documentation_directory(path, ctx.settings)
             {
                 // Recursively search for features in non-documentation subdirectories
-                let new_features = list_files_recursive_impl(
-                    &path,
-                    base_path,
-                    changes_map,
-                    parent_owner,
-                    feature_metadata_map,
-                )?;
+                let new_features = list_files_recursive_impl(path, parent_owner, ctx)?;
                 features.extend(new_features);
             }
         }
@@ -961,14 +863,14 @@ fn list_files_recursive_impl(
 fn get_changes_for_path(
     path: &Path,
     changes_map: &HashMap<String, Vec<Change>>,
+    ctx: &ScanContext,
 ) -> Result<Vec<Change>> {
     // Canonicalize the path
-    let canonical_path = std::fs::canonicalize(path)?;
+    let canonical_path = ctx.tree.source().canonicalize(path)?;
 
-    // Find the repository and get the working directory
-    let repo = Repository::discover(path)?;
-    let repo_workdir = repo
-        .workdir()
+    let repo_workdir = ctx
+        .repo_workdir
+        .as_deref()
         .context("repository has no working directory")?;
 
     // Convert to relative path from repo root
@@ -1021,6 +923,10 @@ mod tests {
             "docs"
         );
 
+        // Test with breaking change marker
+        assert_eq!(extract_commit_type("feat!: drop the v1 API"), "feat");
+        assert_eq!(extract_commit_type("fix(auth)!: reject old tokens"), "fix");
+
         // Test case insensitivity
         assert_eq!(extract_commit_type("FEAT: uppercase type"), "feat");
         assert_eq!(extract_commit_type("Fix: mixed case"), "fix");
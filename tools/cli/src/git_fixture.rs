@@ -0,0 +1,51 @@
+//! Git repositories built by the tests of the modules reading the history
+//!
+//! Every commit is authored by `Dev <dev@example.com>` at the given time, in seconds since
+//! the epoch, so the order of the commits does not depend on how fast the test runs.
+
+use git2::{IndexAddOption, Oid, Repository, Signature, Time};
+use std::fs;
+
+/// Write a file of the working directory, creating its parent folders
+pub fn write_file(repo: &Repository, path: &str, content: &str) {
+    let path = repo.workdir().unwrap().join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+/// Commit the whole working directory on top of HEAD, ignored and deleted files included
+pub fn commit_all(repo: &Repository, message: &str, seconds: i64) -> Oid {
+    let mut index = repo.index().unwrap();
+    index.add_all(["*"], IndexAddOption::FORCE, None).unwrap();
+    index.update_all(["*"], None).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+    let signature = Signature::new("Dev", "dev@example.com", &Time::new(seconds, 0)).unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .unwrap()
+}
+
+/// Write a file and commit it
+pub fn commit_file(repo: &Repository, path: &str, content: &str, seconds: i64) -> Oid {
+    write_file(repo, path, content);
+    commit_all(repo, &format!("feat: update {}", path), seconds)
+}
+
+/// Move a file and commit the move
+pub fn rename_file(repo: &Repository, from: &str, to: &str, seconds: i64) -> Oid {
+    let workdir = repo.workdir().unwrap();
+    fs::create_dir_all(workdir.join(to).parent().unwrap()).unwrap();
+    fs::rename(workdir.join(from), workdir.join(to)).unwrap();
+    commit_all(repo, &format!("refactor: move {} to {}", from, to), seconds)
+}
@@ -1,9 +1,13 @@
 use anyhow::{Context, Result};
-use git2::Repository;
-use std::collections::HashMap;
+use git2::{Delta, DiffFindOptions, DiffOptions, Mailmap, Oid, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
+use crate::commit_links::names_any_feature;
+use crate::config::HistorySettings;
 use crate::models::Change;
+use crate::walker::discover_repository;
 
 /// Get the repository URL from git config.
 /// Tries to get the remote origin URL, returns None if not found.
@@ -27,84 +31,355 @@ fn format_timestamp(time: git2::Time) -> String {
     datetime.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
-/// Get all commits for all paths in the repository at once.
-/// Returns a HashMap where keys are relative paths and values are lists of changes.
-/// This is much more efficient than calling get_commits_for_path for each path individually.
-pub fn get_all_commits_by_path(repo_path: &Path) -> Result<HashMap<String, Vec<Change>>> {
-    let repo = Repository::discover(repo_path).with_context(|| {
-        format!(
-            "failed to discover git repository at `{}`",
-            repo_path.display()
-        )
-    })?;
+/// Commits of a repository with the paths each of them affects, newest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommitHistory {
+    /// Commit the history was read up to
+    pub head: Option<String>,
+    /// Folder the history is limited to, relative to the repository root (empty for all)
+    #[serde(default)]
+    pub subtree: String,
+    /// Limits the history was read with
+    #[serde(default)]
+    pub settings: HistorySettings,
+    /// Author aliases the identities were mapped with, in `.mailmap` format
+    #[serde(default)]
+    pub mailmap: String,
+    /// Normalized names of the features commits outside of `subtree` were kept for
+    #[serde(default)]
+    pub linked_names: BTreeSet<String>,
+    pub commits: Vec<CommitRecord>,
+}
 
-    let mut revwalk = repo.revwalk()?;
-    revwalk.push_head()?;
-    revwalk.set_sorting(git2::Sort::TIME)?;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitRecord {
+    pub change: Change,
+    /// Paths relative to the repository root
+    pub paths: Vec<String>,
+    /// Files the commit renamed or copied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renames: Vec<FileRename>,
+}
 
-    // Map from path to list of changes
-    let mut path_changes: HashMap<String, Vec<Change>> = HashMap::new();
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRename {
+    pub from: String,
+    pub to: String,
+    /// Whether `from` was kept, the file being copied rather than moved
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub copy: bool,
+}
 
-    for oid in revwalk {
-        let oid = oid?;
-        let commit = repo.find_commit(oid)?;
-        let author = commit.author();
-        let message = commit.message().unwrap_or("").to_string();
-
-        // Split message into title and description
-        let lines: Vec<&str> = message.lines().collect();
-        let title = lines.first().unwrap_or(&"").to_string();
-        let description = if lines.len() > 1 {
-            lines[1..].join("\n").trim().to_string()
-        } else {
-            String::new()
-        };
+impl CommitHistory {
+    /// Changes of every directory of the repository, keyed by path relative to its root
+    ///
+    /// Files renamed by a later commit are followed, so a moved folder keeps the history
+    /// it had before the move.
+    pub fn changes_by_path(&self) -> HashMap<String, Vec<Change>> {
+        let mut path_changes: HashMap<String, Vec<Change>> = HashMap::new();
 
-        let change = Change {
-            title,
-            author_name: author.name().unwrap_or("").to_string(),
-            author_email: author.email().unwrap_or("").to_string(),
-            description,
-            date: format_timestamp(commit.time()),
-            hash: format!("{}", oid),
-        };
+        for (commit, paths) in self.commits.iter().zip(self.followed_paths()) {
+            // A commit is added once to every ancestor directory of the files it affects
+            // (not the files themselves, only dirs)
+            let mut directories = HashSet::new();
+            for file_path in &paths {
+                for ancestor in Path::new(file_path).ancestors().skip(1) {
+                    if ancestor == Path::new("") {
+                        break;
+                    }
+                    directories.insert(ancestor.to_string_lossy().to_string());
+                }
+            }
 
-        // Get all paths affected by this commit
-        let affected_paths = get_affected_paths(&repo, &commit)?;
+            for directory in directories {
+                path_changes
+                    .entry(directory)
+                    .or_default()
+                    .push(commit.change.clone());
+            }
+        }
 
-        // For each affected file path, add the change to all ancestor directories
-        // This matches the behavior of commit_affects_path which uses starts_with
-        for file_path in affected_paths {
-            let path_obj = Path::new(&file_path);
+        path_changes
+    }
 
-            // Add to all ancestor directories (not the file itself, only dirs)
-            for ancestor in path_obj.ancestors().skip(1) {
-                if ancestor == Path::new("") {
-                    break;
+    /// Paths affected by each commit, keyed by commit hash, renamed files being followed
+    pub fn paths_by_commit(&self) -> HashMap<&str, Vec<String>> {
+        self.commits
+            .iter()
+            .map(|commit| commit.change.hash.as_str())
+            .zip(self.followed_paths())
+            .collect()
+    }
+
+    /// Number of commits of every file, keyed by path relative to the folder the history is
+    /// limited to, renamed files being followed
+    pub fn commits_by_file(&self) -> HashMap<String, usize> {
+        let mut file_commits: HashMap<String, usize> = HashMap::new();
+
+        for paths in self.followed_paths() {
+            // A file copied to itself is counted once
+            let files: HashSet<&str> = paths
+                .iter()
+                .filter_map(|path| match self.subtree.as_str() {
+                    "" => Some(path.as_str()),
+                    subtree => path.strip_prefix(subtree)?.strip_prefix('/'),
+                })
+                .collect();
+            for file in files {
+                *file_commits.entry(file.to_string()).or_default() += 1;
+            }
+        }
+
+        file_commits
+    }
+
+    /// Paths affected by each commit at their latest location, in the order of the commits
+    ///
+    /// The commits are visited newest first while the latest locations of the renamed files
+    /// are recorded, so an older commit is attributed to where its files live now. A copied
+    /// file is attributed to both its original and its copy.
+    fn followed_paths(&self) -> Vec<Vec<String>> {
+        let mut latest_locations: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut followed_paths = Vec::with_capacity(self.commits.len());
+
+        for commit in &self.commits {
+            let mut paths = Vec::with_capacity(commit.paths.len());
+            for path in &commit.paths {
+                match latest_locations.get(path.as_str()) {
+                    Some(locations) => paths.extend(locations.iter().cloned()),
+                    None => paths.push(path.clone()),
                 }
-                let ancestor_str = ancestor.to_string_lossy().to_string();
+            }
+            followed_paths.push(paths);
 
-                // Only add if not already present (to avoid duplicates)
-                let changes_list = path_changes.entry(ancestor_str).or_default();
-                if !changes_list.iter().any(|c| c.hash == change.hash) {
-                    changes_list.push(change.clone());
+            for rename in &commit.renames {
+                let locations_of = |path: &str| {
+                    latest_locations
+                        .get(path)
+                        .cloned()
+                        .unwrap_or_else(|| vec![path.to_string()])
+                };
+                let mut locations = locations_of(&rename.to);
+                if rename.copy {
+                    locations.extend(locations_of(&rename.from));
                 }
+                latest_locations.insert(rename.from.as_str(), locations);
             }
         }
+
+        followed_paths
     }
+}
+
+/// Read the history of the folder `repo_path` up to `revision` (HEAD by default)
+///
+/// Only the commits touching the folder are kept, within the limits of `settings`, along with
+/// the commits outside of it naming one of `linked_names` in their message (see
+/// `commit_links`), which have no paths and do not count toward `max_commits`. Commits are
+/// walked newest first by commit date and the walk stops at the first one older than
+/// `since`.
+///
+/// Authors are mapped to their canonical identity with the repository `.mailmap` and the
+/// alias file of `settings`.
+///
+/// When `previous` was read with the same folder, limits, aliases and names up to an ancestor of
+/// that commit, only the commits made since are walked and the previous ones are reused.
+/// Otherwise (e.g. after a rebase) the whole history is read again.
+pub fn read_commit_history(
+    repo_path: &Path,
+    previous: Option<CommitHistory>,
+    revision: Option<&str>,
+    settings: &HistorySettings,
+    linked_names: &BTreeSet<String>,
+) -> Result<CommitHistory> {
+    let (repo, subtree) = discover_repository(repo_path)?;
+    let subtree = subtree.to_string_lossy().replace('\\', "/");
+    let since = settings.since_timestamp()?;
+    let until = settings.until_timestamp()?;
 
-    Ok(path_changes)
+    let head = match revision {
+        Some(revision) => repo.revparse_single(revision)?.peel_to_commit()?.id(),
+        None => repo.head()?.peel_to_commit()?.id(),
+    };
+    let mailmap_text = read_mailmap(&repo, head, settings)?;
+    let mailmap = Mailmap::from_buffer(&mailmap_text)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head)?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+    if settings.first_parent {
+        revwalk.simplify_first_parent()?;
+    }
+
+    let mut known_commits = Vec::new();
+    if let Some(previous) = previous
+        && previous.subtree == subtree
+        && previous.settings == *settings
+        && previous.mailmap == mailmap_text
+        && previous.linked_names == *linked_names
+        && let Some(previous_head) = previous.head.as_deref()
+        && let Ok(previous_head) = Oid::from_str(previous_head)
+        && (previous_head == head
+            || repo
+                .graph_descendant_of(head, previous_head)
+                .unwrap_or(false))
+    {
+        revwalk.hide(previous_head)?;
+        known_commits = previous.commits;
+    }
+
+    let mut commits = Vec::new();
+    let mut folder_commits = 0;
+    for oid in revwalk {
+        if settings
+            .max_commits
+            .is_some_and(|max_commits| folder_commits >= max_commits)
+        {
+            break;
+        }
+
+        let commit = repo.find_commit(oid?)?;
+        let time = commit.time().seconds();
+        if until.is_some_and(|until| time > until) {
+            continue;
+        }
+        if since.is_some_and(|since| time < since) {
+            break;
+        }
+
+        let (paths, renames) = get_affected_paths(&repo, &commit, &subtree)?;
+        let change = change_from_commit(&commit, &mailmap);
+        if paths.is_empty() {
+            // Commits outside of the folder are kept when they name one of its features
+            if !names_any_feature(&change, linked_names) {
+                continue;
+            }
+        } else {
+            folder_commits += 1;
+        }
+        commits.push(CommitRecord {
+            change,
+            paths,
+            renames,
+        });
+    }
+
+    if !known_commits.is_empty() && !commits.is_empty() {
+        // Merged branches can bring commits older than the previous head
+        commits.extend(known_commits);
+        commits.sort_by(|a, b| b.change.date.cmp(&a.change.date));
+    } else {
+        commits.extend(known_commits);
+    }
+    if let Some(max_commits) = settings.max_commits {
+        // Keep what a full walk stopping at the last folder commit would have read
+        let mut folder_commits = 0;
+        commits.retain(|commit| {
+            if folder_commits >= max_commits {
+                return false;
+            }
+            if !commit.paths.is_empty() {
+                folder_commits += 1;
+            }
+            true
+        });
+    }
+
+    Ok(CommitHistory {
+        head: Some(head.to_string()),
+        subtree,
+        settings: settings.clone(),
+        mailmap: mailmap_text,
+        linked_names: linked_names.clone(),
+        commits,
+    })
 }
 
-/// Get all paths affected by a commit
-fn get_affected_paths(repo: &Repository, commit: &git2::Commit) -> Result<Vec<String>> {
+/// Whether a path relative to the repository root is `subtree` or inside it
+fn is_in_subtree(path: &str, subtree: &str) -> bool {
+    subtree.is_empty()
+        || path
+            .strip_prefix(subtree)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// The repository `.mailmap`, from the working directory or else from `head`, followed by
+/// the alias file of `settings`
+fn read_mailmap(repo: &Repository, head: Oid, settings: &HistorySettings) -> Result<String> {
+    let workdir_mailmap = repo
+        .workdir()
+        .and_then(|workdir| std::fs::read_to_string(workdir.join(".mailmap")).ok());
+    let mut mailmap = match workdir_mailmap {
+        Some(mailmap) => mailmap,
+        None => repo
+            .find_commit(head)?
+            .tree()?
+            .get_path(Path::new(".mailmap"))
+            .ok()
+            .and_then(|entry| entry.to_object(repo).ok())
+            .and_then(|object| object.peel_to_blob().ok())
+            .map(|blob| String::from_utf8_lossy(blob.content()).to_string())
+            .unwrap_or_default(),
+    };
+
+    if let Some(path) = &settings.mailmap {
+        let aliases = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read mailmap file `{}`", path.display()))?;
+        mailmap.push('\n');
+        mailmap.push_str(&aliases);
+    }
+
+    Ok(mailmap)
+}
+
+fn change_from_commit(commit: &git2::Commit, mailmap: &Mailmap) -> Change {
+    // Authors missing from the mailmap keep their identity
+    let author = commit
+        .author_with_mailmap(mailmap)
+        .unwrap_or_else(|_| commit.author().to_owned());
+    let message = commit.message().unwrap_or("").to_string();
+
+    // Split message into title and description
+    let lines: Vec<&str> = message.lines().collect();
+    let title = lines.first().unwrap_or(&"").to_string();
+    let description = if lines.len() > 1 {
+        lines[1..].join("\n").trim().to_string()
+    } else {
+        String::new()
+    };
+
+    Change {
+        title,
+        author_name: author.name().unwrap_or("").to_string(),
+        author_email: author.email().unwrap_or("").to_string(),
+        description,
+        date: format_timestamp(commit.time()),
+        hash: format!("{}", commit.id()),
+        linked_by_message: false,
+        nested_only: false,
+    }
+}
+
+/// Get all paths affected by a commit inside `subtree`, with the files it renamed or copied
+fn get_affected_paths(
+    repo: &Repository,
+    commit: &git2::Commit,
+    subtree: &str,
+) -> Result<(Vec<String>, Vec<FileRename>)> {
     let mut paths = Vec::new();
+    let mut renames = Vec::new();
 
     // For the first commit (no parents), get all files in the tree
     if commit.parent_count() == 0 {
         let tree = commit.tree()?;
-        collect_tree_paths(repo, &tree, "", &mut paths)?;
-        return Ok(paths);
+        if subtree.is_empty() {
+            collect_tree_paths(repo, &tree, "", &mut paths)?;
+        } else if let Ok(entry) = tree.get_path(Path::new(subtree))
+            && let Ok(subtree_tree) = entry.to_object(repo).and_then(|obj| obj.peel_to_tree())
+        {
+            collect_tree_paths(repo, &subtree_tree, subtree, &mut paths)?;
+        }
+        return Ok((paths, renames));
     }
 
     // For commits with parents, check the diff
@@ -112,10 +387,42 @@ fn get_affected_paths(repo: &Repository, commit: &git2::Commit) -> Result<Vec<St
     let parent = commit.parent(0)?;
     let parent_tree = parent.tree()?;
 
-    let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?;
+    // Limiting the diff to the folder skips the rest of the tree
+    let mut options = DiffOptions::new();
+    if !subtree.is_empty() {
+        options.pathspec(subtree).disable_pathspec_match(true);
+    }
+    let mut diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut options))?;
+
+    // A file added to or deleted from the folder may have been moved from or to the rest of
+    // the tree, which only the whole diff can pair
+    let adds_or_deletes = diff
+        .deltas()
+        .any(|delta| matches!(delta.status(), Delta::Added | Delta::Deleted));
+    if !subtree.is_empty() && adds_or_deletes {
+        diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?;
+    }
+
+    // Pair deleted and added files with similar content as renames
+    let mut find_options = DiffFindOptions::new();
+    find_options.renames(true).copies(true);
+    diff.find_similar(Some(&mut find_options))?;
 
     diff.foreach(
         &mut |delta, _| {
+            if matches!(delta.status(), Delta::Renamed | Delta::Copied)
+                && let (Some(from), Some(to)) = (
+                    delta.old_file().path().and_then(Path::to_str),
+                    delta.new_file().path().and_then(Path::to_str),
+                )
+                && (is_in_subtree(from, subtree) || is_in_subtree(to, subtree))
+            {
+                renames.push(FileRename {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    copy: delta.status() == Delta::Copied,
+                });
+            }
             if let Some(path) = delta.new_file().path()
                 && let Some(path_str) = path.to_str()
             {
@@ -134,7 +441,11 @@ fn get_affected_paths(repo: &Repository, commit: &git2::Commit) -> Result<Vec<St
         None,
     )?;
 
-    Ok(paths)
+    // Files moved across the folder boundary are kept on the side within the folder, their
+    // rename being recorded so older commits follow them
+    paths.retain(|path| is_in_subtree(path, subtree));
+
+    Ok((paths, renames))
 }
 
 /// Recursively collect all paths in a tree
@@ -163,3 +474,299 @@ fn collect_tree_paths(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_fixture::{commit_all, commit_file, rename_file, write_file};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn hashes(history: &CommitHistory) -> Vec<String> {
+        history
+            .commits
+            .iter()
+            .map(|commit| commit.change.hash.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_history_matches_full_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+
+        commit_file(&repo, "features/a/index.ts", "a", 1_000);
+        commit_file(&repo, "features/b/index.ts", "b", 2_000);
+        let settings = HistorySettings::default();
+        let previous =
+            read_commit_history(temp_dir.path(), None, None, &settings, &BTreeSet::new()).unwrap();
+
+        let head = commit_file(&repo, "features/a/index.ts", "a2", 3_000);
+        let incremental = read_commit_history(
+            temp_dir.path(),
+            Some(previous),
+            None,
+            &settings,
+            &BTreeSet::new(),
+        )
+        .unwrap();
+        let full =
+            read_commit_history(temp_dir.path(), None, None, &settings, &BTreeSet::new()).unwrap();
+
+        assert_eq!(incremental.head, Some(head.to_string()));
+        assert_eq!(hashes(&incremental), hashes(&full));
+        assert_eq!(incremental.commits[0].paths, vec!["features/a/index.ts"]);
+
+        let changes = incremental.changes_by_path();
+        assert_eq!(changes["features/a"].len(), 2);
+        assert_eq!(changes["features"].len(), 3);
+        assert_eq!(changes["features/a"][0].hash, head.to_string());
+    }
+
+    #[test]
+    fn test_history_from_unrelated_head_is_read_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "index.ts", "a", 1_000);
+
+        let stale = CommitHistory {
+            head: Some("0123456789012345678901234567890123456789".to_string()),
+            ..CommitHistory::default()
+        };
+        let history = read_commit_history(
+            temp_dir.path(),
+            Some(stale),
+            None,
+            &HistorySettings::default(),
+            &BTreeSet::new(),
+        )
+        .unwrap();
+
+        assert_eq!(history.commits.len(), 1);
+    }
+
+    #[test]
+    fn test_history_follows_renamed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = "export const login = () => {\n  return true;\n};\n";
+        let first = commit_file(&repo, "features/login/index.ts", content, 1_000);
+        let moved = rename_file(
+            &repo,
+            "features/login/index.ts",
+            "features/auth/index.ts",
+            2_000,
+        );
+        // A new folder at the old location does not inherit the old history
+        let recreated = commit_file(&repo, "features/login/index.ts", "new", 3_000);
+
+        let history = read_commit_history(
+            temp_dir.path(),
+            None,
+            None,
+            &HistorySettings::default(),
+            &BTreeSet::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            history.commits[1].renames,
+            vec![FileRename {
+                from: "features/login/index.ts".to_string(),
+                to: "features/auth/index.ts".to_string(),
+                copy: false,
+            }]
+        );
+
+        let changes = history.changes_by_path();
+        let change_hashes = |path: &str| -> Vec<String> {
+            changes[path]
+                .iter()
+                .map(|change| change.hash.clone())
+                .collect()
+        };
+        assert_eq!(
+            change_hashes("features/auth"),
+            vec![moved.to_string(), first.to_string()]
+        );
+        assert_eq!(
+            change_hashes("features/login"),
+            vec![recreated.to_string(), moved.to_string()]
+        );
+
+        let paths = history.paths_by_commit();
+        let first_paths = &paths[first.to_string().as_str()];
+        assert!(first_paths.contains(&"features/auth/index.ts".to_string()));
+        assert!(!first_paths.contains(&"features/login/index.ts".to_string()));
+
+        let file_commits = history.commits_by_file();
+        assert_eq!(file_commits["features/auth/index.ts"], 2);
+        assert_eq!(file_commits["features/login/index.ts"], 2);
+    }
+
+    #[test]
+    fn test_history_follows_files_moved_across_the_scanned_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content = "export const login = () => {\n  return true;\n};\n";
+        let first = commit_file(&repo, "app/features/auth/index.ts", content, 1_000);
+        commit_file(
+            &repo,
+            "lib/session.ts",
+            content.replace("login", "session").as_str(),
+            2_000,
+        );
+        let moved_out = rename_file(&repo, "app/features/auth/index.ts", "lib/auth.ts", 3_000);
+        let moved_in = rename_file(
+            &repo,
+            "lib/session.ts",
+            "app/features/session/index.ts",
+            4_000,
+        );
+
+        let history = read_commit_history(
+            &temp_dir.path().join("app"),
+            None,
+            None,
+            &HistorySettings::default(),
+            &BTreeSet::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            hashes(&history),
+            vec![
+                moved_in.to_string(),
+                moved_out.to_string(),
+                first.to_string()
+            ]
+        );
+        assert_eq!(
+            history.commits[0].renames,
+            vec![FileRename {
+                from: "lib/session.ts".to_string(),
+                to: "app/features/session/index.ts".to_string(),
+                copy: false,
+            }]
+        );
+        assert_eq!(
+            history.commits[0].paths,
+            vec!["app/features/session/index.ts"]
+        );
+        assert_eq!(
+            history.commits[1].renames,
+            vec![FileRename {
+                from: "app/features/auth/index.ts".to_string(),
+                to: "lib/auth.ts".to_string(),
+                copy: false,
+            }]
+        );
+        assert_eq!(history.commits[1].paths, vec!["app/features/auth/index.ts"]);
+
+        // The commits of a file moved out of the folder follow it out
+        let file_commits = history.commits_by_file();
+        assert_eq!(file_commits.get("features/auth/index.ts"), Some(&1));
+        assert_eq!(file_commits.get("features/session/index.ts"), Some(&1));
+    }
+
+    #[test]
+    fn test_authors_are_mapped_to_their_canonical_identity() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        commit_file(&repo, "index.ts", "a", 1_000);
+        fs::write(
+            temp_dir.path().join(".mailmap"),
+            "Jane Doe <jane@example.com> <dev@example.com>\n",
+        )
+        .unwrap();
+
+        let settings = HistorySettings::default();
+        let history =
+            read_commit_history(temp_dir.path(), None, None, &settings, &BTreeSet::new()).unwrap();
+        let author = &history.commits[0].change;
+        assert_eq!(author.author_name, "Jane Doe");
+        assert_eq!(author.author_email, "jane@example.com");
+
+        // The alias file comes last and wins, and a cached history is mapped again
+        let aliases = temp_dir.path().join("authors.mailmap");
+        fs::write(&aliases, "Jane <jane@corp.example.com> <dev@example.com>\n").unwrap();
+        let settings = HistorySettings {
+            mailmap: Some(aliases),
+            ..HistorySettings::default()
+        };
+        let history = read_commit_history(
+            temp_dir.path(),
+            Some(history),
+            None,
+            &settings,
+            &BTreeSet::new(),
+        )
+        .unwrap();
+        let author = &history.commits[0].change;
+        assert_eq!(author.author_name, "Jane");
+        assert_eq!(author.author_email, "jane@corp.example.com");
+    }
+
+    #[test]
+    fn test_history_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let first = commit_file(&repo, "features/a/index.ts", "a", 1_000);
+        commit_file(&repo, "features/ab/index.ts", "ab", 2_000);
+        let third = commit_file(&repo, "features/a/index.ts", "a2", 3_000);
+        let fourth = commit_file(&repo, "features/a/index.ts", "a3", 4_000);
+        let feature_a = temp_dir.path().join("features/a");
+
+        // Only the commits touching the scanned folder are read
+        let settings = HistorySettings::default();
+        let history =
+            read_commit_history(&feature_a, None, None, &settings, &BTreeSet::new()).unwrap();
+        assert_eq!(history.subtree, "features/a");
+        assert_eq!(
+            hashes(&history),
+            vec![fourth.to_string(), third.to_string(), first.to_string()]
+        );
+
+        let settings = HistorySettings {
+            since: Some("1970-01-01 00:25:00".to_string()),
+            until: Some("1970-01-01 01:00:00".to_string()),
+            ..HistorySettings::default()
+        };
+        let history =
+            read_commit_history(&feature_a, None, None, &settings, &BTreeSet::new()).unwrap();
+        assert_eq!(hashes(&history), vec![third.to_string()]);
+
+        let settings = HistorySettings {
+            max_commits: Some(2),
+            ..HistorySettings::default()
+        };
+        let history =
+            read_commit_history(&feature_a, None, None, &settings, &BTreeSet::new()).unwrap();
+        assert_eq!(
+            hashes(&history),
+            vec![fourth.to_string(), third.to_string()]
+        );
+
+        // Commits outside of the folder naming one of its features do not count as its commits
+        write_file(&repo, "config/a.yml", "a");
+        let linked = commit_all(&repo, "chore: tune a\n\nFeature: a", 5_000);
+        write_file(&repo, "config/deps.yml", "b");
+        commit_all(&repo, "fix(deps): bump", 6_000);
+        let names = BTreeSet::from(["a".to_string()]);
+        let history = read_commit_history(&feature_a, None, None, &settings, &names).unwrap();
+        assert_eq!(
+            hashes(&history),
+            vec![linked.to_string(), fourth.to_string(), third.to_string()]
+        );
+        assert!(history.commits[0].paths.is_empty());
+
+        // A history read with other limits is not reused
+        let history = read_commit_history(
+            &feature_a,
+            Some(history),
+            None,
+            &HistorySettings::default(),
+            &BTreeSet::new(),
+        )
+        .unwrap();
+        assert_eq!(history.commits.len(), 3);
+    }
+}
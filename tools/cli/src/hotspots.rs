@@ -0,0 +1,265 @@
+//! Module for the hotspots of features and files
+//!
+//! A hotspot is code that changes often and is large: its score is its number of commits
+//! multiplied by its number of lines, so refactoring can start where it pays the most. Files
+//! are scored from the commits touching them, renamed files being followed, and features
+//! from their own commits (nested features excluded) and their line count. The history read
+//! (see the `since` and `until` history settings) is the time window of the scores.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::feature_detection::find_feature_for_path;
+use crate::models::{Feature, flatten_features};
+
+/// Number of files listed in the hotspots of each feature, highest score first
+const LISTED_HOTSPOTS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileHotspot {
+    /// Path relative to the scanned folder
+    pub path: String,
+    pub commits: usize,
+    pub lines: usize,
+    /// Commits multiplied by lines
+    pub score: usize,
+}
+
+impl FileHotspot {
+    pub fn new(path: String, commits: usize, lines: usize) -> Self {
+        Self {
+            path,
+            commits,
+            lines,
+            score: commits * lines,
+        }
+    }
+}
+
+/// Files of a feature with the highest scores, files without commits or lines left out
+pub fn top_hotspots(mut files: Vec<FileHotspot>) -> Vec<FileHotspot> {
+    files.retain(|file| file.score > 0);
+    sort_by_score(&mut files, |file| (file.score, file.commits, &file.path));
+    files.truncate(LISTED_HOTSPOTS);
+    files
+}
+
+/// Hotspots of a scan, as shown by the `hotspots` command
+#[derive(Debug, Clone, Serialize)]
+pub struct HotspotReport {
+    pub features: Vec<FeatureHotspot>,
+    pub files: Vec<RankedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureHotspot {
+    pub name: String,
+    pub path: String,
+    pub commits: usize,
+    pub lines: usize,
+    pub score: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RankedFile {
+    /// Path of the innermost feature the file belongs to, unset for files outside of features
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+    #[serde(flatten)]
+    pub file: FileHotspot,
+}
+
+/// The `limit` features and files with the highest scores
+///
+/// Files are ranked among every file of the scanned folder (see
+/// `list_files_recursive_with_hotspots`), not only the hotspots listed in the stats of each
+/// feature.
+pub fn build_report(features: &[Feature], files: Vec<FileHotspot>, limit: usize) -> HotspotReport {
+    let mut report = HotspotReport {
+        features: Vec::new(),
+        files: Vec::new(),
+    };
+    for feature in flatten_features(features) {
+        let Some(stats) = &feature.stats else {
+            continue;
+        };
+
+        let commits = stats
+            .commits
+            .get("total_commits")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0) as usize;
+        let lines = stats.lines_count.unwrap_or(0);
+        if commits * lines > 0 {
+            report.features.push(FeatureHotspot {
+                name: feature.name.clone(),
+                path: feature.path.clone(),
+                commits,
+                lines,
+                score: commits * lines,
+            });
+        }
+    }
+
+    report.files = files
+        .into_iter()
+        .filter(|file| file.score > 0)
+        .map(|file| RankedFile {
+            feature: find_feature_for_path(&file.path, features).map(|f| f.path.clone()),
+            file,
+        })
+        .collect();
+
+    sort_by_score(&mut report.features, |feature| {
+        (feature.score, feature.commits, &feature.path)
+    });
+    sort_by_score(&mut report.files, |ranked| {
+        (ranked.file.score, ranked.file.commits, &ranked.file.path)
+    });
+    report.features.truncate(limit);
+    report.files.truncate(limit);
+    report
+}
+
+/// Highest score first, then most commits, then by path
+fn sort_by_score<T>(items: &mut [T], key: impl Fn(&T) -> (usize, usize, &String)) {
+    items.sort_by(|a, b| {
+        let (a_score, a_commits, a_path) = key(a);
+        let (b_score, b_commits, b_path) = key(b);
+        b_score
+            .cmp(&a_score)
+            .then_with(|| b_commits.cmp(&a_commits))
+            .then_with(|| a_path.cmp(b_path))
+    });
+}
+
+/// Two tables, features then files, highest score first
+pub fn format_text(report: &HotspotReport) -> String {
+    let mut output = String::new();
+
+    let _ = writeln!(output, "Features");
+    let _ = writeln!(
+        output,
+        "{:>10} {:>8} {:>8}  PATH",
+        "SCORE", "COMMITS", "LINES"
+    );
+    for feature in &report.features {
+        let _ = writeln!(
+            output,
+            "{:>10} {:>8} {:>8}  {}",
+            feature.score, feature.commits, feature.lines, feature.path
+        );
+    }
+
+    let _ = writeln!(output, "\nFiles");
+    let _ = writeln!(
+        output,
+        "{:>10} {:>8} {:>8}  PATH",
+        "SCORE", "COMMITS", "LINES"
+    );
+    for ranked in &report.files {
+        let file = &ranked.file;
+        let _ = writeln!(
+            output,
+            "{:>10} {:>8} {:>8}  {}",
+            file.score, file.commits, file.lines, file.path
+        );
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Stats;
+    use std::collections::BTreeMap;
+
+    fn feature(path: &str, commits: usize, lines: usize) -> Feature {
+        let mut commit_stats = BTreeMap::new();
+        if commits > 0 {
+            commit_stats.insert("total_commits".to_string(), serde_json::json!(commits));
+        }
+
+        Feature {
+            stats: Some(Stats {
+                lines_count: Some(lines),
+                commits: commit_stats,
+                ..Stats::default()
+            }),
+            ..Feature::at(path)
+        }
+    }
+
+    #[test]
+    fn test_top_hotspots() {
+        let files = (1..=7)
+            .map(|index| FileHotspot::new(format!("src/{}.rs", index), index, 10))
+            .chain([FileHotspot::new("src/empty.rs".to_string(), 9, 0)])
+            .collect();
+
+        let hotspots = top_hotspots(files);
+
+        let paths: Vec<&str> = hotspots.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["src/7.rs", "src/6.rs", "src/5.rs", "src/4.rs", "src/3.rs"]
+        );
+        assert_eq!(hotspots[0].score, 70);
+    }
+
+    #[test]
+    fn test_build_report() {
+        let mut auth = feature("features/auth", 4, 100);
+        auth.features = vec![feature("features/auth/features/sso", 10, 50)];
+        let features = vec![auth, feature("features/docs", 0, 300)];
+        let files = vec![
+            FileHotspot::new("features/auth/login.rs".to_string(), 4, 60),
+            FileHotspot::new("features/auth/features/sso/saml.rs".to_string(), 10, 50),
+            FileHotspot::new("scripts/release.sh".to_string(), 12, 30),
+            FileHotspot::new("features/docs/guide.md".to_string(), 0, 300),
+        ];
+
+        let report = build_report(&features, files.clone(), 10);
+
+        let scores: Vec<(&str, usize)> = report
+            .features
+            .iter()
+            .map(|feature| (feature.path.as_str(), feature.score))
+            .collect();
+        assert_eq!(
+            scores,
+            vec![("features/auth/features/sso", 500), ("features/auth", 400)]
+        );
+        let ranked: Vec<(&str, Option<&str>, usize)> = report
+            .files
+            .iter()
+            .map(|ranked| {
+                (
+                    ranked.file.path.as_str(),
+                    ranked.feature.as_deref(),
+                    ranked.file.score,
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                (
+                    "features/auth/features/sso/saml.rs",
+                    Some("features/auth/features/sso"),
+                    500
+                ),
+                ("scripts/release.sh", None, 360),
+                ("features/auth/login.rs", Some("features/auth"), 240),
+            ]
+        );
+
+        let report = build_report(&features, files, 1);
+        assert_eq!((report.features.len(), report.files.len()), (1, 1));
+        assert!(
+            format_text(&report)
+                .contains("       500       10       50  features/auth/features/sso\n")
+        );
+    }
+}
@@ -10,6 +10,7 @@
 
 use anyhow::Result;
 use include_dir::{Dir, include_dir};
+use indicatif::ProgressBar;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -18,6 +19,8 @@ use tokio::sync::RwLock;
 use tokio::time::sleep;
 use warp::{Filter, Reply};
 
+use crate::cache::CACHE_DIR_NAME;
+use crate::config::ScanSettings;
 use crate::file_scanner::list_files_recursive_with_changes;
 use crate::git_helper::get_repository_url;
 use crate::models::Feature;
@@ -69,6 +72,7 @@ impl ServerConfig {
 /// * `watch_path` - Path to watch for file changes
 /// * `on_ready` - Optional callback to be called when server is ready
 /// * `skip_changes` - Whether changes were skipped during feature computation
+/// * `settings` - Scan settings used when features are recomputed
 ///
 /// # Returns
 ///
@@ -79,6 +83,7 @@ pub async fn serve_features_with_watching(
     watch_path: PathBuf,
     on_ready: Option<Box<dyn FnOnce() + Send>>,
     skip_changes: bool,
+    settings: ScanSettings,
 ) -> Result<()> {
     let config = ServerConfig::new(port);
     serve_features_with_config_and_watching(
@@ -87,6 +92,7 @@ pub async fn serve_features_with_watching(
         Some(watch_path.clone()),
         on_ready,
         skip_changes,
+        settings,
     )
     .await
 }
@@ -100,6 +106,7 @@ pub async fn serve_features_with_watching(
 /// * `watch_path` - Optional path to watch for file changes
 /// * `on_ready` - Optional callback to be called when server is ready
 /// * `skip_changes` - Whether changes were skipped during feature computation
+/// * `settings` - Scan settings used when features are recomputed
 ///
 /// # Returns
 ///
@@ -110,6 +117,7 @@ pub async fn serve_features_with_config_and_watching(
     watch_path: Option<PathBuf>,
     on_ready: Option<Box<dyn FnOnce() + Send>>,
     skip_changes: bool,
+    settings: ScanSettings,
 ) -> Result<()> {
     // Create shared state for features
     let features_data = Arc::new(RwLock::new(features.to_vec()));
@@ -125,7 +133,9 @@ pub async fn serve_features_with_config_and_watching(
         let watch_path_clone = path.clone();
 
         tokio::spawn(async move {
-            if let Err(e) = setup_file_watcher(features_data_clone, watch_path_clone).await {
+            if let Err(e) =
+                setup_file_watcher(features_data_clone, watch_path_clone, settings).await
+            {
                 eprintln!("File watcher error: {}", e);
             }
         });
@@ -226,6 +236,7 @@ pub async fn serve_features_with_config_and_watching(
 async fn setup_file_watcher(
     features_data: Arc<RwLock<Vec<Feature>>>,
     watch_path: PathBuf,
+    settings: ScanSettings,
 ) -> Result<()> {
     let (tx, mut rx) = tokio::sync::mpsc::channel(100);
 
@@ -255,19 +266,27 @@ async fn setup_file_watcher(
     // Process file system events
     while let Some(event) = rx.recv().await {
         // Check if this is a file we care about (README.md files or directory changes)
+        // Writes to the scan cache are ignored, they would trigger a scan after every scan
         let should_recompute = event.paths.iter().any(|path| {
-            path.file_name()
-                .map(|name| name == "README.md")
-                .unwrap_or(false)
-                || event.kind.is_create()
-                || event.kind.is_remove()
+            let in_cache = path
+                .components()
+                .any(|component| component.as_os_str() == CACHE_DIR_NAME);
+
+            !in_cache
+                && (path
+                    .file_name()
+                    .map(|name| name == "README.md")
+                    .unwrap_or(false)
+                    || event.kind.is_create()
+                    || event.kind.is_remove())
         });
 
         if should_recompute {
             // Add a small delay to avoid excessive recomputation during rapid changes
             sleep(Duration::from_millis(500)).await;
 
-            match list_files_recursive_with_changes(&watch_path) {
+            match list_files_recursive_with_changes(&watch_path, &settings, &ProgressBar::hidden())
+            {
                 Ok(new_features) => {
                     let mut features = features_data.write().await;
                     *features = new_features;
@@ -3,13 +3,12 @@
 //! This module scans source files for import statements and resolves them
 //! to their actual file paths to detect cross-feature dependencies.
 
-use anyhow::Result;
 use std::collections::HashMap;
-use std::fs;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
-#[derive(Debug, Clone)]
+use crate::walker::{FileTree, Source};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ImportStatement {
     pub file_path: String,
     pub line_number: usize,
@@ -304,18 +303,16 @@ fn extract_import(line: &str, pattern: &ImportPattern) -> Option<String> {
     }
 }
 
-/// Scan a single file for import statements
-pub fn scan_file_for_imports(file_path: &Path) -> Result<Vec<ImportStatement>> {
+/// Extract the import statements of a file from its content
+pub fn extract_imports(file_path: &Path, content: &str) -> Vec<ImportStatement> {
     let mut imports = Vec::new();
 
     let extension = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
     let pattern = match get_import_pattern(extension) {
         Some(p) => p,
-        None => return Ok(imports), // Unsupported file type
+        None => return imports, // Unsupported file type
     };
 
-    let content = fs::read_to_string(file_path)?;
-
     for (line_number, line) in content.lines().enumerate() {
         if let Some(imported_path) = extract_import(line, &pattern) {
             imports.push(ImportStatement {
@@ -327,46 +324,17 @@ pub fn scan_file_for_imports(file_path: &Path) -> Result<Vec<ImportStatement>> {
         }
     }
 
-    Ok(imports)
+    imports
 }
 
 /// Build a map of all files in the project for quick lookup
-pub fn build_file_map(base_path: &Path) -> HashMap<String, PathBuf> {
+pub fn build_file_map(tree: &FileTree) -> HashMap<String, PathBuf> {
     let mut file_map = HashMap::new();
 
-    let skip_dirs = [
-        "node_modules",
-        "target",
-        "dist",
-        "build",
-        ".git",
-        ".svn",
-        ".hg",
-        "vendor",
-        "__pycache__",
-        ".next",
-        ".nuxt",
-        "coverage",
-    ];
-
-    for entry in WalkDir::new(base_path)
-        .into_iter()
-        .filter_entry(|e| {
-            if e.file_type().is_dir() {
-                let dir_name = e.file_name().to_string_lossy();
-                !skip_dirs.contains(&dir_name.as_ref())
-            } else {
-                true
-            }
-        })
-        .filter_map(|e| e.ok())
-    {
-        if entry.file_type().is_file() {
-            let path = entry.path();
-            if let Ok(relative_path) = path.strip_prefix(base_path) {
-                let key = relative_path.to_string_lossy().to_string();
-                file_map.insert(key, path.to_path_buf());
-            }
+    for path in tree.files() {
+        if let Ok(relative_path) = path.strip_prefix(tree.root()) {
+            let key = relative_path.to_string_lossy().to_string();
+            file_map.insert(key, path.clone());
         }
     }
 
@@ -379,16 +347,17 @@ pub fn resolve_import_path(
     source_file: &Path,
     base_path: &Path,
     file_map: &HashMap<String, PathBuf>,
+    source: &Source,
 ) -> Option<PathBuf> {
     let source_dir = source_file.parent()?;
 
     // Handle different types of imports
     if import_path.starts_with('.') {
         // Relative import (./file or ../file)
-        resolve_relative_import(import_path, source_dir, base_path)
+        resolve_relative_import(import_path, source_dir, base_path, source)
     } else if import_path.contains("::") {
         // Rust-style module path (crate::module::submodule)
-        resolve_rust_module_path(import_path, source_file, base_path, file_map)
+        resolve_rust_module_path(import_path, source_file, base_path, file_map, source)
     } else if import_path.contains('/') {
         // Path-like import
         resolve_path_import(import_path, base_path, file_map)
@@ -403,6 +372,7 @@ fn resolve_relative_import(
     import_path: &str,
     source_dir: &Path,
     base_path: &Path,
+    source: &Source,
 ) -> Option<PathBuf> {
     let _import_path_clean = import_path
         .trim_start_matches("./")
@@ -413,13 +383,13 @@ fn resolve_relative_import(
 
     // First, check if the path is a directory and try index files
     // This handles: import foo from './folder' -> './folder/index.ts'
-    if candidate.is_dir() {
+    if source.is_dir(&candidate) {
         for index_name in ["index", "mod", "__init__"] {
             for idx_ext in ["ts", "tsx", "js", "jsx", "rs", "py"] {
                 let index_path = candidate.join(format!("{}.{}", index_name, idx_ext));
-                if index_path.exists() && index_path.starts_with(base_path) {
+                if source.exists(&index_path) && index_path.starts_with(base_path) {
                     // Canonicalize to resolve .. and . in the path
-                    if let Ok(canonical) = index_path.canonicalize() {
+                    if let Ok(canonical) = source.canonicalize(&index_path) {
                         return Some(canonical);
                     }
                     return Some(index_path);
@@ -443,9 +413,9 @@ fn resolve_relative_import(
             PathBuf::from(path_str)
         };
 
-        if path_with_ext.exists() && path_with_ext.starts_with(base_path) {
+        if source.exists(&path_with_ext) && path_with_ext.starts_with(base_path) {
             // Canonicalize to resolve .. and . in the path
-            if let Ok(canonical) = path_with_ext.canonicalize() {
+            if let Ok(canonical) = source.canonicalize(&path_with_ext) {
                 return Some(canonical);
             }
             return Some(path_with_ext);
@@ -453,11 +423,11 @@ fn resolve_relative_import(
 
         // Also check if path with extension is a directory with index file
         // This handles edge cases like './folder.component' -> './folder.component/index.ts'
-        if path_with_ext.is_dir() {
+        if source.is_dir(&path_with_ext) {
             for index_name in ["index", "mod", "__init__"] {
                 for idx_ext in ["ts", "tsx", "js", "jsx", "rs", "py"] {
                     let index_path = path_with_ext.join(format!("{}.{}", index_name, idx_ext));
-                    if index_path.exists() && index_path.starts_with(base_path) {
+                    if source.exists(&index_path) && index_path.starts_with(base_path) {
                         return Some(index_path);
                     }
                 }
@@ -474,13 +444,14 @@ fn resolve_rust_module_path(
     source_file: &Path,
     base_path: &Path,
     _file_map: &HashMap<String, PathBuf>,
+    source: &Source,
 ) -> Option<PathBuf> {
     // Convert crate::module::submodule to module/submodule.rs or module/submodule/mod.rs
     let path_str = if let Some(stripped) = import_path.strip_prefix("crate::") {
         stripped
     } else if let Some(stripped) = import_path.strip_prefix("super::") {
         // Handle super:: by going up one directory
-        return resolve_super_path(stripped, source_file, base_path);
+        return resolve_super_path(stripped, source_file, base_path, source);
     } else {
         import_path.strip_prefix("self::")?
     };
@@ -489,7 +460,7 @@ fn resolve_rust_module_path(
     let path_parts: Vec<&str> = path_str.split("::").collect();
 
     // Find src directory
-    let src_dir = find_src_directory(base_path)?;
+    let src_dir = find_src_directory(base_path, source)?;
 
     // Try module/file.rs
     let mut module_path = src_dir.clone();
@@ -497,13 +468,13 @@ fn resolve_rust_module_path(
         module_path = module_path.join(part);
     }
 
-    if module_path.with_extension("rs").exists() {
+    if source.exists(&module_path.with_extension("rs")) {
         return Some(module_path.with_extension("rs"));
     }
 
     // Try module/mod.rs
     let mod_path = module_path.join("mod.rs");
-    if mod_path.exists() {
+    if source.exists(&mod_path) {
         return Some(mod_path);
     }
 
@@ -515,6 +486,7 @@ fn resolve_super_path(
     remaining_path: &str,
     source_file: &Path,
     base_path: &Path,
+    source: &Source,
 ) -> Option<PathBuf> {
     let source_dir = source_file.parent()?;
     let parent_dir = source_dir.parent()?;
@@ -530,12 +502,12 @@ fn resolve_super_path(
         module_path = module_path.join(part);
     }
 
-    if module_path.with_extension("rs").exists() {
+    if source.exists(&module_path.with_extension("rs")) {
         return Some(module_path.with_extension("rs"));
     }
 
     let mod_path = module_path.join("mod.rs");
-    if mod_path.exists() {
+    if source.exists(&mod_path) {
         return Some(mod_path);
     }
 
@@ -543,18 +515,17 @@ fn resolve_super_path(
 }
 
 /// Find the src directory in a Rust project
-fn find_src_directory(base_path: &Path) -> Option<PathBuf> {
+fn find_src_directory(base_path: &Path, source: &Source) -> Option<PathBuf> {
     let src_dir = base_path.join("src");
-    if src_dir.is_dir() {
+    if source.is_dir(&src_dir) {
         return Some(src_dir);
     }
 
     // Look for src in subdirectories
-    for entry in (fs::read_dir(base_path).ok()?).flatten() {
-        let path = entry.path();
-        if path.is_dir() {
+    for path in source.read_dir(base_path).ok()? {
+        if source.is_dir(&path) {
             let nested_src = path.join("src");
-            if nested_src.is_dir() {
+            if source.is_dir(&nested_src) {
                 return Some(nested_src);
             }
         }
@@ -1,15 +1,33 @@
+pub mod affected;
 pub mod build;
+pub mod cache;
+pub mod changelog;
+pub mod check_report;
 pub mod checker;
 pub mod codeowners;
+pub mod commit_links;
+pub mod config;
+pub mod contributors;
+pub mod coupling;
 pub mod coverage_parser;
 pub mod dependency_resolver;
+pub mod diff;
+pub mod feature_detection;
 pub mod feature_metadata_detector;
 pub mod features_toml_parser;
 pub mod file_scanner;
+#[cfg(test)]
+mod git_fixture;
 pub mod git_helper;
+pub mod hotspots;
 pub mod http_server;
 pub mod import_detector;
 pub mod models;
+pub mod owner_suggestion;
+pub mod owners;
 pub mod printer;
 pub mod readme_parser;
+pub mod releases;
 pub mod scan;
+pub mod timeline;
+pub mod walker;
@@ -1,18 +1,31 @@
 use std::collections::BTreeMap;
 
+use crate::contributors::ContributorStats;
+use crate::coupling::CoupledFeature;
 use crate::coverage_parser::CoverageStats;
+use crate::hotspots::FileHotspot;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Change {
     pub title: String,
+    /// Canonical name of the author, mapped with the `.mailmap` and the alias file
     pub author_name: String,
+    /// Canonical email of the author
     pub author_email: String,
     pub description: String,
     pub date: String,
     pub hash: String,
+    /// Whether the commit is attached to the feature by its message (a `Feature:` trailer or a
+    /// conventional commit scope) rather than by the files it touches
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub linked_by_message: bool,
+    /// Whether the commit only touches files of nested features: it is listed with the
+    /// feature but left out of its own metrics
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub nested_only: bool,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct Stats {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub files_count: Option<usize>,
@@ -21,6 +34,14 @@ pub struct Stats {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub todos_count: Option<usize>,
     pub commits: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contributors: Option<ContributorStats>,
+    /// Features most often changed in the same commits, strongest coupling first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coupling: Vec<CoupledFeature>,
+    /// Own files changed most often multiplied by their size, highest score first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hotspots: Vec<FileHotspot>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub coverage: Option<CoverageStats>,
 }
@@ -42,7 +63,49 @@ pub struct Feature {
     pub dependencies: Vec<Dependency>,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+impl Feature {
+    /// Changes of the feature's own files, newest first, leaving out the commits that only
+    /// touch its nested features
+    pub fn own_changes(&self) -> Vec<&Change> {
+        self.changes
+            .iter()
+            .filter(|change| !change.nested_only)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl Feature {
+    /// Feature named after the last folder of its path, with nothing else set, to be completed
+    /// with the struct update syntax
+    pub fn at(path: &str) -> Self {
+        Self {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            description: String::new(),
+            owner: String::new(),
+            is_owner_inherited: false,
+            path: path.to_string(),
+            features: Vec::new(),
+            meta: BTreeMap::new(),
+            changes: Vec::new(),
+            decisions: Vec::new(),
+            stats: None,
+            dependencies: Vec::new(),
+        }
+    }
+}
+
+/// Every feature of a tree, each one followed by its nested features
+pub fn flatten_features(features: &[Feature]) -> Vec<&Feature> {
+    let mut flat_features = Vec::new();
+    for feature in features {
+        flat_features.push(feature);
+        flat_features.extend(flatten_features(&feature.features));
+    }
+    flat_features
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DependencyType {
     Parent,
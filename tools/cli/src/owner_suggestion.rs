@@ -0,0 +1,385 @@
+//! Module for the `suggest-owners` command
+//!
+//! The owner of a feature is suggested from the authors of its most recent commits: each
+//! author is mapped to a team with the teams file and the team with the most commits wins,
+//! the authors themselves being suggested without a teams file. Only the features without
+//! owner or with an inherited one are looked at, unless every feature is asked for.
+//!
+//! A suggestion is written in the FEATURES.toml of the feature when it has one, else in the
+//! front matter of its README, else in a new FEATURES.toml.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::feature_detection::find_readme_file;
+use crate::features_toml_parser::{FEATURES_TOML_NAMES, find_features_toml};
+use crate::models::{Change, Feature, flatten_features};
+use crate::walker::Source;
+
+/// Teams of the commit authors, read from a TOML file listing the members of each team
+///
+/// ```toml
+/// team-payments = ["alice@example.com", "Bob Martin"]
+/// team-auth = ["carol@example.com"]
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TeamMap {
+    /// Team of each member, keyed by lowercase email or name
+    teams: HashMap<String, String>,
+}
+
+impl TeamMap {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("could not read teams file `{}`", path.display()))?;
+        let members: BTreeMap<String, Vec<String>> = toml::from_str(&content)
+            .with_context(|| format!("could not parse teams file `{}`", path.display()))?;
+
+        let mut teams = HashMap::new();
+        for (team, members) in members {
+            for member in members {
+                teams.insert(member.trim().to_lowercase(), team.clone());
+            }
+        }
+        Ok(Self { teams })
+    }
+
+    /// Team of the author of a change, found by email first then by name
+    fn team_of(&self, change: &Change) -> Option<&str> {
+        [&change.author_email, &change.author_name]
+            .into_iter()
+            .find_map(|identity| self.teams.get(&identity.to_lowercase()))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerSuggestion {
+    pub name: String,
+    pub path: String,
+    pub current_owner: String,
+    pub is_owner_inherited: bool,
+    pub suggested_owner: String,
+    /// Recent commits made by the suggested owner
+    pub commits: usize,
+    /// Recent commits considered
+    pub recent_commits: usize,
+    /// Share of the recent commits made by the suggested owner, in percent
+    pub share_percent: f64,
+}
+
+/// Suggest an owner for the features from their `recent_commits` last commits, sorted by path
+///
+/// Features already owned by the suggested owner, and features without commits by a known
+/// team, get no suggestion.
+pub fn suggest_owners(
+    features: &[Feature],
+    teams: Option<&TeamMap>,
+    recent_commits: usize,
+    all: bool,
+) -> Vec<OwnerSuggestion> {
+    let mut suggestions = Vec::new();
+    for feature in flatten_features(features) {
+        let explicitly_owned = !feature.owner.is_empty() && !feature.is_owner_inherited;
+        if explicitly_owned && !all {
+            continue;
+        }
+
+        let changes: Vec<&Change> = feature
+            .own_changes()
+            .into_iter()
+            .take(recent_commits)
+            .collect();
+        let Some((owner, commits)) = most_active_owner(&changes, teams) else {
+            continue;
+        };
+        if owner == feature.owner {
+            continue;
+        }
+
+        let share = commits as f64 * 100.0 / changes.len() as f64;
+        suggestions.push(OwnerSuggestion {
+            name: feature.name.clone(),
+            path: feature.path.clone(),
+            current_owner: feature.owner.clone(),
+            is_owner_inherited: feature.is_owner_inherited,
+            suggested_owner: owner,
+            commits,
+            recent_commits: changes.len(),
+            share_percent: (share * 100.0).round() / 100.0,
+        });
+    }
+
+    suggestions.sort_by(|a, b| a.path.cmp(&b.path));
+    suggestions
+}
+
+/// Owner with the most commits, the one who committed last winning ties
+fn most_active_owner(changes: &[&Change], teams: Option<&TeamMap>) -> Option<(String, usize)> {
+    // Commits and position of the most recent commit of every owner
+    let mut owners: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (position, change) in changes.iter().enumerate() {
+        let owner = match teams {
+            Some(teams) => match teams.team_of(change) {
+                Some(team) => team,
+                None => continue,
+            },
+            None => change.author_name.as_str(),
+        };
+        owners.entry(owner).or_insert((0, position)).0 += 1;
+    }
+
+    owners
+        .into_iter()
+        .max_by(
+            |(_, (a_commits, a_position)), (_, (b_commits, b_position))| {
+                a_commits
+                    .cmp(b_commits)
+                    .then_with(|| b_position.cmp(a_position))
+            },
+        )
+        .map(|(owner, (commits, _))| (owner.to_string(), commits))
+}
+
+/// Write the owner of the feature in `feature_dir`, returning the file written
+pub fn write_owner(feature_dir: &Path, owner: &str) -> Result<PathBuf> {
+    let source = Source::WorkingDirectory;
+
+    let (path, content) = if let Some(path) = find_features_toml(feature_dir, &source) {
+        let content = read(&path)?;
+        let content = set_toml_owner(&content, owner);
+        (path, content)
+    } else if let Some(path) = find_readme_file(feature_dir, &source) {
+        let content = read(&path)?;
+        let content = set_front_matter_owner(&content, owner);
+        (path, content)
+    } else {
+        let path = feature_dir.join(FEATURES_TOML_NAMES[0]);
+        (path, set_toml_owner("", owner))
+    };
+
+    fs::write(&path, content).with_context(|| format!("could not write `{}`", path.display()))?;
+    Ok(path)
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("could not read `{}`", path.display()))
+}
+
+/// Replace the top-level `owner` key of a TOML document, or add it after `name`
+fn set_toml_owner(content: &str, owner: &str) -> String {
+    let owner_line = format!("owner = {}", toml::Value::String(owner.to_string()));
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+
+    // Top-level keys come before the first table header
+    let top_level_end = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let key_line = |key: &str| {
+        lines[..top_level_end].iter().position(|line| {
+            line.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+    };
+
+    match (key_line("owner"), key_line("name")) {
+        (Some(index), _) => lines[index] = owner_line,
+        (None, Some(index)) => lines.insert(index + 1, owner_line),
+        (None, None) => lines.insert(0, owner_line),
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Replace the `owner` key of the front matter of a README, adding the front matter if needed
+fn set_front_matter_owner(content: &str, owner: &str) -> String {
+    // A JSON string is a valid double-quoted YAML string
+    let owner_line = format!(
+        "owner: {}",
+        serde_json::to_string(owner).unwrap_or_default()
+    );
+
+    let Some((front_matter, markdown)) = content
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---\n"))
+    else {
+        return format!("---\n{}\n---\n\n{}", owner_line, content);
+    };
+
+    let mut lines: Vec<String> = front_matter.lines().map(String::from).collect();
+    match lines.iter().position(|line| line.starts_with("owner:")) {
+        Some(index) => lines[index] = owner_line,
+        None => lines.push(owner_line),
+    }
+
+    format!("---\n{}\n---\n{}", lines.join("\n"), markdown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::readme_parser::read_readme_info;
+    use tempfile::TempDir;
+
+    fn change(author: &str, email: &str) -> Change {
+        Change {
+            title: "feat: update".to_string(),
+            author_name: author.to_string(),
+            author_email: email.to_string(),
+            description: String::new(),
+            date: "2025-01-01 00:00:00".to_string(),
+            hash: String::new(),
+            linked_by_message: false,
+            nested_only: false,
+        }
+    }
+
+    fn feature(path: &str, owner: &str, inherited: bool, changes: Vec<Change>) -> Feature {
+        Feature {
+            owner: owner.to_string(),
+            is_owner_inherited: inherited,
+            changes,
+            ..Feature::at(path)
+        }
+    }
+
+    #[test]
+    fn test_suggest_owners() {
+        let temp_dir = TempDir::new().unwrap();
+        let teams_path = temp_dir.path().join("teams.toml");
+        fs::write(
+            &teams_path,
+            "team-a = [\"alice@example.com\"]\nteam-b = [\"Bob\", \"carol@example.com\"]\n",
+        )
+        .unwrap();
+        let teams = TeamMap::load(&teams_path).unwrap();
+
+        let commits = vec![
+            change("Alice", "Alice@example.com"),
+            change("Bob", "bob@example.com"),
+            change("Carol", "carol@example.com"),
+            change("Alice", "alice@example.com"),
+            change("Zoe", "zoe@example.com"),
+            // Older than the recent commits considered
+            change("Alice", "alice@example.com"),
+            change("Alice", "alice@example.com"),
+        ];
+        let mut parent = feature("features/pay", "team-c", false, commits.clone());
+        parent.features = vec![
+            feature(
+                "features/pay/features/refund",
+                "team-c",
+                true,
+                commits.clone(),
+            ),
+            feature(
+                "features/pay/features/card",
+                "",
+                false,
+                commits[..1].to_vec(),
+            ),
+        ];
+        let features = vec![parent, feature("features/docs", "", false, Vec::new())];
+
+        let suggestions = suggest_owners(&features, Some(&teams), 5, false);
+
+        let suggested: Vec<(&str, &str, usize, usize)> = suggestions
+            .iter()
+            .map(|s| {
+                let owner = s.suggested_owner.as_str();
+                (s.path.as_str(), owner, s.commits, s.recent_commits)
+            })
+            .collect();
+        assert_eq!(
+            suggested,
+            vec![
+                ("features/pay/features/card", "team-a", 1, 1),
+                // Both teams made 2 of the 5 recent commits, Alice committed last
+                ("features/pay/features/refund", "team-a", 2, 5),
+            ]
+        );
+        assert_eq!(suggestions[1].share_percent, 40.0);
+
+        // Features with an explicit owner are only looked at on demand
+        let suggestions = suggest_owners(&features, Some(&teams), 5, true);
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(suggestions[0].path, "features/pay");
+
+        // Without teams file, the authors are suggested
+        let suggestions = suggest_owners(&features, None, 5, false);
+        assert_eq!(suggestions[1].suggested_owner, "Alice");
+
+        // Commits only touching nested features are left out
+        let nested_only = |change: Change| Change {
+            nested_only: true,
+            ..change
+        };
+        let pay = feature(
+            "features/pay",
+            "",
+            false,
+            vec![
+                nested_only(change("Alice", "alice@example.com")),
+                nested_only(change("Alice", "alice@example.com")),
+                change("Bob", "bob@example.com"),
+            ],
+        );
+        let suggestions = suggest_owners(&[pay], Some(&teams), 5, false);
+        assert_eq!(suggestions[0].suggested_owner, "team-b");
+        assert_eq!(suggestions[0].recent_commits, 1);
+    }
+
+    #[test]
+    fn test_write_owner() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = Source::WorkingDirectory;
+
+        let with_toml = temp_dir.path().join("with-toml");
+        fs::create_dir_all(&with_toml).unwrap();
+        fs::write(
+            with_toml.join("FEATURES.toml"),
+            "name = \"Payments\"\n\n[links]\nowner = \"not the owner\"\n",
+        )
+        .unwrap();
+        let path = write_owner(&with_toml, "@team-a").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "name = \"Payments\"\nowner = \"@team-a\"\n\n[links]\nowner = \"not the owner\"\n"
+        );
+        let path = write_owner(&with_toml, "@team-b").unwrap();
+        let manifest = crate::features_toml_parser::read_features_toml(&path, &source).unwrap();
+        assert_eq!(manifest.owner.as_deref(), Some("@team-b"));
+
+        let with_front_matter = temp_dir.path().join("with-front-matter");
+        fs::create_dir_all(&with_front_matter).unwrap();
+        fs::write(
+            with_front_matter.join("README.md"),
+            "---\nowner: old\nstatus: beta\n---\n# Refunds\n",
+        )
+        .unwrap();
+        let path = write_owner(&with_front_matter, "@team-a").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "---\nowner: \"@team-a\"\nstatus: beta\n---\n# Refunds\n"
+        );
+
+        let with_readme = temp_dir.path().join("with-readme");
+        fs::create_dir_all(&with_readme).unwrap();
+        fs::write(with_readme.join("README.md"), "# Cards\n\nCard payments.\n").unwrap();
+        let path = write_owner(&with_readme, "team \"a\"").unwrap();
+        let readme = read_readme_info(&path, &source).unwrap();
+        assert_eq!(readme.owner, "team \"a\"");
+        assert_eq!(readme.title.as_deref(), Some("Cards"));
+
+        let empty = temp_dir.path().join("empty");
+        fs::create_dir_all(&empty).unwrap();
+        let path = write_owner(&empty, "team-a").unwrap();
+        assert_eq!(path, empty.join("FEATURES.toml"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "owner = \"team-a\"\n");
+    }
+}
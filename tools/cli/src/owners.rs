@@ -0,0 +1,265 @@
+//! Module for looking up the owners of many paths at once
+//!
+//! Features are indexed by path once, then each path is resolved by looking up its
+//! ancestors from the innermost one, so the innermost feature wins as in
+//! [`find_feature_for_path`](crate::feature_detection::find_feature_for_path). Paths do
+//! not need to exist, which lets deleted files from a diff be resolved too. Relative paths
+//! are relative to the current directory, or to the root of the repository (as printed by
+//! `git diff --name-only`) once the index is anchored there.
+
+use anyhow::{Context, Result};
+use git2::Repository;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::feature_detection::normalize_path;
+use crate::models::Feature;
+
+/// Features of a scan keyed by their path relative to the scanned folder
+pub struct OwnerIndex<'a> {
+    base_path: PathBuf,
+    /// Folder relative paths are resolved against
+    root: PathBuf,
+    features: HashMap<String, &'a Feature>,
+}
+
+impl<'a> OwnerIndex<'a> {
+    pub fn new(features: &'a [Feature], base_path: &Path) -> Result<Self> {
+        let base_path = base_path
+            .canonicalize()
+            .with_context(|| format!("could not read directory `{}`", base_path.display()))?;
+        let root = std::env::current_dir()?;
+
+        let mut index = HashMap::new();
+        index_features(features, &mut index);
+
+        Ok(Self {
+            base_path,
+            root,
+            features: index,
+        })
+    }
+
+    /// Resolve relative paths against the root of the repository of the scanned folder
+    /// rather than the current directory, outside of a repository the current directory is
+    /// kept
+    pub fn relative_to_repository_root(mut self) -> Result<Self> {
+        if let Some(workdir) = Repository::discover(&self.base_path)
+            .ok()
+            .and_then(|repo| repo.workdir().map(Path::to_path_buf))
+        {
+            self.root = workdir.canonicalize()?;
+        }
+        Ok(self)
+    }
+
+    /// Innermost feature containing a path, relative to the current directory (see
+    /// `relative_to_repository_root`) or absolute
+    pub fn find(&self, path: &Path) -> Option<&'a Feature> {
+        let resolved_path = resolve_path(&self.root.join(path))?;
+        let relative_path = resolved_path.strip_prefix(&self.base_path).ok()?;
+
+        relative_path
+            .ancestors()
+            .find_map(|ancestor| {
+                let key = normalize_path(&ancestor.to_string_lossy());
+                self.features.get(&key)
+            })
+            .copied()
+    }
+}
+
+fn index_features<'a>(features: &'a [Feature], index: &mut HashMap<String, &'a Feature>) {
+    for feature in features {
+        index.insert(normalize_path(&feature.path), feature);
+        index_features(&feature.features, index);
+    }
+}
+
+/// Resolve the symlinks of an absolute path, for its longest existing ancestor only
+fn resolve_path(path: &Path) -> Option<PathBuf> {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(canonical_path) = existing.canonicalize() {
+            return Some(
+                missing
+                    .iter()
+                    .rev()
+                    .fold(canonical_path, |path, name| path.join(name)),
+            );
+        }
+        missing.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+/// A looked up path and the feature it belongs to
+#[derive(Debug, Clone, Serialize)]
+pub struct PathOwner {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_path: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub inherited: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerGroup {
+    pub owner: String,
+    pub paths: Vec<PathOwner>,
+}
+
+/// Paths grouped by owner, sorted by owner name
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnersReport {
+    pub owners: Vec<OwnerGroup>,
+    /// Paths outside of any feature or in a feature without owner
+    pub unowned: Vec<PathOwner>,
+}
+
+/// Look up the owner of every path, in input order and without duplicates
+pub fn group_paths_by_owner(index: &OwnerIndex, paths: &[String]) -> OwnersReport {
+    let mut owners: BTreeMap<String, Vec<PathOwner>> = BTreeMap::new();
+    let mut unowned = Vec::new();
+    let mut seen = HashSet::new();
+
+    for path in paths {
+        if !seen.insert(path) {
+            continue;
+        }
+
+        let feature = index.find(Path::new(path));
+        let path_owner = PathOwner {
+            path: path.clone(),
+            feature_name: feature.map(|f| f.name.clone()),
+            feature_path: feature.map(|f| f.path.clone()),
+            inherited: feature.is_some_and(|f| f.is_owner_inherited),
+        };
+
+        match feature {
+            Some(feature) if !feature.owner.is_empty() => owners
+                .entry(feature.owner.clone())
+                .or_default()
+                .push(path_owner),
+            _ => unowned.push(path_owner),
+        }
+    }
+
+    OwnersReport {
+        owners: owners
+            .into_iter()
+            .map(|(owner, paths)| OwnerGroup { owner, paths })
+            .collect(),
+        unowned,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn feature(path: &str, owner: &str, features: Vec<Feature>) -> Feature {
+        Feature {
+            owner: owner.to_string(),
+            features,
+            ..Feature::at(path)
+        }
+    }
+
+    fn features() -> Vec<Feature> {
+        vec![
+            feature(
+                "features/a",
+                "team-a",
+                vec![feature("features/a/features/b", "team-b", vec![])],
+            ),
+            feature("features/ab", "", vec![]),
+        ]
+    }
+
+    #[test]
+    fn test_find_resolves_missing_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("features/a/features/b")).unwrap();
+        fs::create_dir_all(base.join("features/ab")).unwrap();
+
+        let features = features();
+        let index = OwnerIndex::new(&features, base).unwrap();
+        let find = |path: &str| index.find(&base.join(path)).map(|f| f.path.as_str());
+
+        assert_eq!(find("features/a/index.ts"), Some("features/a"));
+        assert_eq!(find("features/a/deleted/file.ts"), Some("features/a"));
+        assert_eq!(
+            find("features/a/features/b/x.ts"),
+            Some("features/a/features/b")
+        );
+        assert_eq!(find("features/ab/x.ts"), Some("features/ab"));
+        assert_eq!(find("features/a"), Some("features/a"));
+        assert_eq!(find("other/x.ts"), None);
+    }
+
+    #[test]
+    fn test_group_paths_by_owner() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path();
+        fs::create_dir_all(base.join("features")).unwrap();
+
+        let features = features();
+        let index = OwnerIndex::new(&features, base).unwrap();
+        let path = |p: &str| base.join(p).to_string_lossy().to_string();
+
+        let report = group_paths_by_owner(
+            &index,
+            &[
+                path("features/a/features/b/x.ts"),
+                path("features/a/x.ts"),
+                path("features/ab/x.ts"),
+                path("shared/x.ts"),
+                path("features/a/x.ts"),
+            ],
+        );
+
+        let owners: Vec<(&str, usize)> = report
+            .owners
+            .iter()
+            .map(|group| (group.owner.as_str(), group.paths.len()))
+            .collect();
+        assert_eq!(owners, vec![("team-a", 1), ("team-b", 1)]);
+
+        assert_eq!(report.unowned.len(), 2);
+        assert_eq!(
+            report.unowned[0].feature_path.as_deref(),
+            Some("features/ab")
+        );
+        assert_eq!(report.unowned[1].feature_path, None);
+    }
+
+    #[test]
+    fn test_find_resolves_paths_from_repository_root() {
+        let temp_dir = TempDir::new().unwrap();
+        Repository::init(temp_dir.path()).unwrap();
+        let base = temp_dir.path().join("app");
+        fs::create_dir_all(base.join("features/a/features/b")).unwrap();
+
+        let features = features();
+        let index = OwnerIndex::new(&features, &base)
+            .unwrap()
+            .relative_to_repository_root()
+            .unwrap();
+        let find = |path: &str| index.find(Path::new(path)).map(|f| f.path.as_str());
+
+        assert_eq!(find("app/features/a/index.ts"), Some("features/a"));
+        assert_eq!(
+            find("app/features/a/features/b/deleted.ts"),
+            Some("features/a/features/b")
+        );
+        assert_eq!(find("features/a/index.ts"), None);
+    }
+}
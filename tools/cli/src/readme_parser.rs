@@ -1,8 +1,9 @@
 use anyhow::{Context, Result};
 use std::collections::BTreeMap;
-use std::fs;
 use std::path::Path;
 
+use crate::walker::Source;
+
 /// Information extracted from a README file
 pub struct ReadmeInfo {
     /// Optional title extracted from the first markdown heading
@@ -57,8 +58,8 @@ fn read_readme_content(content: &str) -> String {
 /// Reads README information from README.md or README.mdx files
 /// Returns ReadmeInfo containing title, owner, description, and metadata
 /// The title is extracted from the first markdown heading (# Title)
-pub fn read_readme_info(readme_path: &Path) -> Result<ReadmeInfo> {
-    if !readme_path.exists() {
+pub fn read_readme_info(readme_path: &Path, source: &Source) -> Result<ReadmeInfo> {
+    if !source.exists(readme_path) {
         return Ok(ReadmeInfo {
             title: None,
             owner: "".to_string(),
@@ -67,7 +68,8 @@ pub fn read_readme_info(readme_path: &Path) -> Result<ReadmeInfo> {
         });
     }
 
-    let content = fs::read_to_string(readme_path)
+    let content = source
+        .read_to_string(readme_path)
         .with_context(|| format!("could not read README file at `{}`", readme_path.display()))?;
 
     let mut title: Option<String> = None;
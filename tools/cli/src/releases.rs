@@ -0,0 +1,263 @@
+//! Module for the `releases` command
+//!
+//! Every tag reachable from the scanned revision is a release, made of the commits since the
+//! previous tag. The features changed by each release are listed with their own commits counted
+//! by conventional commit type and the authors involved. Features are those of the scanned
+//! revision, so features removed before it are not reported.
+
+use anyhow::{Context, Result};
+use git2::{Oid, Repository};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::file_scanner::extract_commit_type;
+use crate::models::{Feature, flatten_features};
+use crate::timeline::{SampledRevision, Sampling, sample_revisions};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Release {
+    /// Tag of the release
+    pub name: String,
+    /// Tag of the previous release, `None` for the first one
+    pub previous: Option<String>,
+    pub commit: String,
+    pub date: String,
+    /// Features changed by the release, sorted by path
+    pub features: Vec<FeatureImpact>,
+}
+
+/// Changes of a feature in a release
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureImpact {
+    pub name: String,
+    pub path: String,
+    pub owner: String,
+    pub commits: usize,
+    /// Commits by conventional commit type (`feat`, `fix`, ..., `other`)
+    pub commit_types: BTreeMap<String, usize>,
+    /// Authors of the commits, most commits first
+    pub authors: Vec<String>,
+}
+
+/// Tags reachable from `revision` (HEAD by default) with the commits of each, oldest first
+pub fn read_releases(
+    base_path: &Path,
+    revision: Option<&str>,
+) -> Result<Vec<(SampledRevision, HashSet<String>)>> {
+    let repo = Repository::discover(base_path).with_context(|| {
+        format!(
+            "failed to discover git repository at `{}`",
+            base_path.display()
+        )
+    })?;
+    let tags = sample_revisions(base_path, revision, Sampling::Tags, usize::MAX)?;
+
+    let mut releases = Vec::new();
+    let mut previous: Option<Oid> = None;
+    for tag in tags {
+        let commit = Oid::from_str(&tag.commit)?;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(commit)?;
+        if let Some(previous) = previous {
+            revwalk.hide(previous)?;
+        }
+        let commits = revwalk
+            .map(|oid| Ok(oid?.to_string()))
+            .collect::<Result<HashSet<String>>>()?;
+
+        previous = Some(commit);
+        releases.push((tag, commits));
+    }
+
+    Ok(releases)
+}
+
+/// Impact of every release on the features, newest release first
+pub fn build_releases(
+    features: &[Feature],
+    releases: Vec<(SampledRevision, HashSet<String>)>,
+) -> Vec<Release> {
+    let mut result = Vec::new();
+    let mut previous: Option<String> = None;
+
+    for (tag, commits) in releases {
+        let mut impacts = Vec::new();
+        for feature in flatten_features(features) {
+            let mut commit_types: BTreeMap<String, usize> = BTreeMap::new();
+            let mut authors: HashMap<&str, usize> = HashMap::new();
+            let mut count = 0;
+            for change in feature.own_changes() {
+                if !commits.contains(&change.hash) {
+                    continue;
+                }
+                count += 1;
+                *commit_types
+                    .entry(extract_commit_type(&change.title))
+                    .or_default() += 1;
+                *authors.entry(change.author_name.as_str()).or_default() += 1;
+            }
+            if count == 0 {
+                continue;
+            }
+
+            let mut authors: Vec<(&str, usize)> = authors.into_iter().collect();
+            authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            impacts.push(FeatureImpact {
+                name: feature.name.clone(),
+                path: feature.path.clone(),
+                owner: feature.owner.clone(),
+                commits: count,
+                commit_types,
+                authors: authors
+                    .into_iter()
+                    .map(|(author, _)| author.to_string())
+                    .collect(),
+            });
+        }
+        impacts.sort_by(|a, b| a.path.cmp(&b.path));
+
+        result.push(Release {
+            previous: previous.replace(tag.name.clone()),
+            name: tag.name,
+            commit: tag.commit,
+            date: tag.date,
+            features: impacts,
+        });
+    }
+
+    result.reverse();
+    result
+}
+
+/// One section per release listing the features it changed
+pub fn format_text(releases: &[Release]) -> String {
+    let mut output = String::new();
+
+    for (index, release) in releases.iter().enumerate() {
+        if index > 0 {
+            output.push('\n');
+        }
+        let date = release.date.split(' ').next().unwrap_or(&release.date);
+        match &release.previous {
+            Some(previous) => {
+                let _ = writeln!(output, "{} ({}, since {})", release.name, date, previous);
+            }
+            None => {
+                let _ = writeln!(output, "{} ({})", release.name, date);
+            }
+        }
+
+        if release.features.is_empty() {
+            output.push_str("  No feature changes.\n");
+        }
+        for feature in &release.features {
+            let types: Vec<String> = feature
+                .commit_types
+                .iter()
+                .map(|(commit_type, count)| format!("{} {}", commit_type, count))
+                .collect();
+            let _ = writeln!(
+                output,
+                "  {} ({}): {} commits ({}) by {}",
+                feature.name,
+                feature.path,
+                feature.commits,
+                types.join(", "),
+                feature.authors.join(", ")
+            );
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Change;
+
+    fn change(hash: &str, title: &str, author: &str) -> Change {
+        Change {
+            title: title.to_string(),
+            author_name: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            description: String::new(),
+            date: "2025-01-01 00:00:00".to_string(),
+            hash: hash.to_string(),
+            linked_by_message: false,
+            nested_only: false,
+        }
+    }
+
+    fn feature(path: &str, changes: Vec<Change>) -> Feature {
+        Feature {
+            owner: "team-a".to_string(),
+            changes,
+            ..Feature::at(path)
+        }
+    }
+
+    fn tag(name: &str, commits: &[&str]) -> (SampledRevision, HashSet<String>) {
+        let revision = SampledRevision {
+            name: name.to_string(),
+            commit: format!("{}-commit", name),
+            date: "2025-01-01 10:00:00".to_string(),
+        };
+        (
+            revision,
+            commits.iter().map(|hash| hash.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_build_releases() {
+        let features = vec![
+            feature(
+                "features/auth",
+                vec![
+                    // Only touching a nested feature of auth
+                    Change {
+                        nested_only: true,
+                        ..change("c5", "feat: add passkeys", "Carol")
+                    },
+                    change("c4", "fix(login): handle expired tokens", "Bob"),
+                    change("c3", "feat: add SSO", "Alice"),
+                    change("c2", "feat: add login", "Bob"),
+                    change("c1", "chore: init", "Alice"),
+                ],
+            ),
+            feature("features/docs", vec![change("c1", "chore: init", "Alice")]),
+        ];
+        let releases = vec![
+            tag("v1.0", &["c1"]),
+            tag("v1.1", &["c2", "c3", "c4"]),
+            tag("v1.2", &["c5"]),
+        ];
+
+        let releases = build_releases(&features, releases);
+
+        let names: Vec<&str> = releases
+            .iter()
+            .map(|release| release.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["v1.2", "v1.1", "v1.0"]);
+        assert!(releases[0].features.is_empty());
+        assert_eq!(releases[1].previous.as_deref(), Some("v1.0"));
+        assert_eq!(releases[2].previous, None);
+
+        let auth = &releases[1].features[0];
+        assert_eq!(auth.commits, 3);
+        assert_eq!(
+            auth.commit_types,
+            BTreeMap::from([("feat".to_string(), 2), ("fix".to_string(), 1)])
+        );
+        assert_eq!(auth.authors, vec!["Bob", "Alice"]);
+        assert_eq!(releases[2].features.len(), 2);
+
+        let text = format_text(&releases);
+        assert!(text.contains("v1.2 (2025-01-01, since v1.1)\n  No feature changes.\n"));
+        assert!(text.contains("  auth (features/auth): 3 commits (feat 2, fix 1) by Bob, Alice\n"));
+    }
+}
@@ -5,8 +5,10 @@
 //! easy-to-use API.
 
 use anyhow::Result;
+use indicatif::ProgressBar;
 use std::path::Path;
 
+use crate::config::ScanSettings;
 use crate::coverage_parser::{self, map_coverage_to_features, parse_coverage_reports};
 use crate::file_scanner::{list_files_recursive, list_files_recursive_with_changes};
 use crate::models::Feature;
@@ -29,6 +31,12 @@ pub struct ScanConfig<'a> {
 
     /// Optional project directory (used for finding coverage)
     pub project_dir: Option<&'a Path>,
+
+    /// Lists used to classify directories (documentation, skipped, features folder)
+    pub settings: ScanSettings,
+
+    /// Progress bar advanced for every file read, hidden by default
+    pub progress: ProgressBar,
 }
 
 impl<'a> ScanConfig<'a> {
@@ -40,6 +48,8 @@ impl<'a> ScanConfig<'a> {
             coverage_dir_override: None,
             current_dir,
             project_dir: None,
+            settings: ScanSettings::default(),
+            progress: ProgressBar::hidden(),
         }
     }
 
@@ -66,6 +76,18 @@ impl<'a> ScanConfig<'a> {
         self.project_dir = Some(dir);
         self
     }
+
+    /// Set the lists used to classify directories
+    pub fn settings(mut self, settings: ScanSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set the progress bar advanced while files are read
+    pub fn progress(mut self, progress: ProgressBar) -> Self {
+        self.progress = progress;
+        self
+    }
 }
 
 /// Scan features in a directory with the given configuration
@@ -96,9 +118,9 @@ impl<'a> ScanConfig<'a> {
 pub fn scan_features(base_path: &Path, config: ScanConfig) -> Result<Vec<Feature>> {
     // Step 1: Scan features with or without git history
     let mut features = if config.skip_changes {
-        list_files_recursive(base_path)?
+        list_files_recursive(base_path, &config.settings, &config.progress)?
     } else {
-        list_files_recursive_with_changes(base_path)?
+        list_files_recursive_with_changes(base_path, &config.settings, &config.progress)?
     };
 
     // Step 2: Add coverage if requested
@@ -187,6 +209,9 @@ fn update_features_with_coverage(
                     lines_count: None,
                     todos_count: None,
                     commits: std::collections::BTreeMap::new(),
+                    contributors: None,
+                    coupling: Vec::new(),
+                    hotspots: Vec::new(),
                     coverage: Some(coverage.clone()),
                 });
             }
@@ -200,6 +225,7 @@ fn update_features_with_coverage(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git_fixture::{commit_all, write_file};
     use std::path::PathBuf;
 
     #[test]
@@ -228,6 +254,142 @@ mod tests {
         assert!(config.project_dir.is_none());
     }
 
+    #[test]
+    fn test_scan_features_with_custom_containers_and_patterns() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for dir in [
+            "modules/billing/domains/invoices",
+            "features/ignored",
+            "apps/web/src",
+        ] {
+            std::fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+
+        let mut settings = ScanSettings::default();
+        settings.containers = vec!["modules".to_string(), "domains".to_string()];
+        settings.patterns = vec!["apps/*".to_string()];
+        let config = ScanConfig::new(temp_dir.path())
+            .skip_changes(true)
+            .settings(settings);
+
+        let features = scan_features(temp_dir.path(), config).unwrap();
+        let paths: Vec<&str> = features.iter().map(|f| f.path.as_str()).collect();
+
+        assert_eq!(paths, vec!["apps/web", "modules/billing"]);
+        assert_eq!(
+            features[1].features[0].path,
+            "modules/billing/domains/invoices"
+        );
+    }
+
+    #[test]
+    fn test_scan_features_of_a_revision() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path();
+        let repo = git2::Repository::init(base).unwrap();
+        write_file(
+            &repo,
+            "features/a/README.md",
+            "---\nowner: team-a\n---\n# A\n",
+        );
+        write_file(&repo, "features/a/index.ts", "import { b } from '../b';\n");
+        write_file(&repo, "features/b/index.ts", "export const b = 1;\n");
+        write_file(&repo, "features/b/.docs/decisions/001.md", "# Use b\n");
+        write_file(&repo, "legacy/features/c/index.ts", "export const c = 1;\n");
+        let commit = commit_all(&repo, "feat: add a and b", 1_000);
+
+        std::fs::remove_dir_all(base.join("features/b")).unwrap();
+        std::fs::remove_dir_all(base.join("legacy")).unwrap();
+        write_file(
+            &repo,
+            "features/a/README.md",
+            "---\nowner: team-c\n---\n# A\n",
+        );
+
+        let mut settings = ScanSettings::default();
+        settings.revision = Some(commit.to_string());
+        let config = ScanConfig::new(base).settings(settings.clone());
+        let features = scan_features(base, config).unwrap();
+
+        let paths: Vec<&str> = features.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["features/a", "features/b", "legacy/features/c"]);
+        assert_eq!(features[0].owner, "team-a");
+        assert_eq!(features[0].dependencies[0].feature_path, "features/b");
+        assert_eq!(features[0].changes[0].title, "feat: add a and b");
+        assert_eq!(features[1].decisions, vec!["# Use b\n"]);
+        assert_eq!(features[1].stats.as_ref().unwrap().lines_count, Some(1));
+
+        // A folder deleted since the revision is scanned from it, history included
+        let legacy = base.join("legacy");
+        let config = ScanConfig::new(&legacy).settings(settings);
+        let features = scan_features(&legacy, config).unwrap();
+
+        assert_eq!(features[0].path, "features/c");
+        assert_eq!(features[0].changes[0].title, "feat: add a and b");
+    }
+
+    #[test]
+    fn test_scan_features_flags_changes_of_nested_features_only() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path();
+        let repo = git2::Repository::init(base).unwrap();
+        write_file(&repo, "features/pay/index.ts", "pay");
+        write_file(&repo, "features/pay/features/card/index.ts", "card");
+        commit_all(&repo, "feat: add payments", 1_000);
+        write_file(&repo, "features/pay/features/card/index.ts", "card expiry");
+        commit_all(&repo, "fix: card expiry", 2_000);
+
+        let config = ScanConfig::new(base).skip_changes(false);
+        let features = scan_features(base, config).unwrap();
+
+        let pay = &features[0];
+        assert_eq!(pay.changes.len(), 2);
+        assert!(pay.changes[0].nested_only);
+        let own_titles: Vec<&str> = pay
+            .own_changes()
+            .iter()
+            .map(|change| change.title.as_str())
+            .collect();
+        assert_eq!(own_titles, vec!["feat: add payments"]);
+        let total_commits = &pay.stats.as_ref().unwrap().commits["total_commits"];
+        assert_eq!(total_commits, &serde_json::json!(1));
+        assert_eq!(pay.features[0].own_changes().len(), 2);
+    }
+
+    #[test]
+    fn test_scan_features_links_commits_outside_of_the_scanned_folder() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path();
+        let repo = git2::Repository::init(base).unwrap();
+        write_file(&repo, "app/features/checkout/index.ts", "checkout");
+        commit_all(&repo, "feat: add checkout", 1_000);
+        write_file(&repo, "config/timeouts.yml", "timeout: 30");
+        commit_all(
+            &repo,
+            "chore: raise payment timeouts\n\nFeature: checkout",
+            2_000,
+        );
+        write_file(&repo, "config/logging.yml", "level: debug");
+        commit_all(&repo, "chore: enable debug logs", 3_000);
+
+        let config = ScanConfig::new(base).skip_changes(false);
+        let features = scan_features(&base.join("app"), config).unwrap();
+
+        let checkout = &features[0];
+        let titles: Vec<(&str, bool)> = checkout
+            .changes
+            .iter()
+            .map(|change| (change.title.as_str(), change.linked_by_message))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("chore: raise payment timeouts", true),
+                ("feat: add checkout", false)
+            ]
+        );
+    }
+
     #[test]
     fn test_scan_features_basic() {
         let test_path = PathBuf::from("../../examples/tests_skip_changes/src");
@@ -0,0 +1,319 @@
+//! Module for the `timeline` command
+//!
+//! Revisions are sampled from the history (every tag, or one commit every few weeks along the
+//! first parents) and each of them is scanned straight from its git tree, without checking it
+//! out. The metrics of every feature at each revision make a time series showing how the
+//! feature grew or shrank. Features are identified by path, so a moved feature starts a new
+//! series.
+
+use anyhow::{Context, Result};
+use git2::{Repository, Sort};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::models::{Feature, flatten_features};
+
+const SECONDS_PER_WEEK: i64 = 7 * 24 * 60 * 60;
+
+/// How revisions are picked from the history
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sampling {
+    /// Every tag pointing to an ancestor of the last revision
+    Tags,
+    /// The last commit of every period of this many weeks, along the first parents
+    Weeks(u32),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SampledRevision {
+    /// Tag name, or short commit hash
+    pub name: String,
+    pub commit: String,
+    pub date: String,
+}
+
+/// Metrics of a feature at a revision
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureSample {
+    /// Name of the revision
+    pub revision: String,
+    pub owner: String,
+    pub files: usize,
+    pub lines: usize,
+    pub todos: usize,
+    pub dependencies: usize,
+}
+
+/// Metrics of a feature at every sampled revision where it exists, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureTimeline {
+    pub name: String,
+    pub path: String,
+    pub samples: Vec<FeatureSample>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Timeline {
+    /// Sampled revisions, oldest first
+    pub revisions: Vec<SampledRevision>,
+    /// Features sorted by path
+    pub features: Vec<FeatureTimeline>,
+}
+
+/// At most `max_samples` revisions up to `revision` (HEAD by default), oldest first
+pub fn sample_revisions(
+    base_path: &Path,
+    revision: Option<&str>,
+    sampling: Sampling,
+    max_samples: usize,
+) -> Result<Vec<SampledRevision>> {
+    let repo = Repository::discover(base_path).with_context(|| {
+        format!(
+            "failed to discover git repository at `{}`",
+            base_path.display()
+        )
+    })?;
+    let head = repo
+        .revparse_single(revision.unwrap_or("HEAD"))
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| {
+            format!(
+                "could not resolve revision `{}`",
+                revision.unwrap_or("HEAD")
+            )
+        })?;
+
+    let mut samples = Vec::new();
+    match sampling {
+        Sampling::Tags => {
+            let mut tagged = Vec::new();
+            for name in repo.tag_names(None)?.iter().flatten() {
+                let Ok(commit) = repo
+                    .revparse_single(&format!("refs/tags/{}", name))
+                    .and_then(|object| object.peel_to_commit())
+                else {
+                    continue;
+                };
+                let reachable =
+                    commit.id() == head.id() || repo.graph_descendant_of(head.id(), commit.id())?;
+                if reachable {
+                    tagged.push((commit.time().seconds(), name.to_string(), commit));
+                }
+            }
+            tagged.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.cmp(&a.1)));
+
+            for (_, name, commit) in tagged.into_iter().take(max_samples) {
+                samples.push(sampled(name, &commit));
+            }
+        }
+        Sampling::Weeks(weeks) => {
+            let period = i64::from(weeks.max(1)) * SECONDS_PER_WEEK;
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push(head.id())?;
+            revwalk.simplify_first_parent()?;
+            revwalk.set_sorting(Sort::TIME)?;
+
+            // The end of the next period to sample, walking back from the last revision
+            let mut period_end = head.time().seconds();
+            for oid in revwalk {
+                if samples.len() >= max_samples {
+                    break;
+                }
+                let commit = repo.find_commit(oid?)?;
+                let time = commit.time().seconds();
+                if time > period_end {
+                    continue;
+                }
+
+                let short_hash = commit.id().to_string()[..7].to_string();
+                samples.push(sampled(short_hash, &commit));
+                while period_end >= time {
+                    period_end -= period;
+                }
+            }
+        }
+    }
+
+    samples.reverse();
+    Ok(samples)
+}
+
+fn sampled(name: String, commit: &git2::Commit) -> SampledRevision {
+    let date = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+
+    SampledRevision {
+        name,
+        commit: commit.id().to_string(),
+        date,
+    }
+}
+
+/// Time series of every feature, from the features scanned at each revision
+pub fn build_timeline(scans: Vec<(SampledRevision, Vec<Feature>)>) -> Timeline {
+    let mut revisions = Vec::new();
+    let mut features: BTreeMap<String, FeatureTimeline> = BTreeMap::new();
+
+    for (revision, scanned) in scans {
+        for feature in flatten_features(&scanned) {
+            let stats = feature.stats.as_ref();
+            let count = |value: Option<usize>| value.unwrap_or(0);
+            let timeline =
+                features
+                    .entry(feature.path.clone())
+                    .or_insert_with(|| FeatureTimeline {
+                        name: feature.name.clone(),
+                        path: feature.path.clone(),
+                        samples: Vec::new(),
+                    });
+            // The latest name of the feature is kept
+            timeline.name = feature.name.clone();
+            timeline.samples.push(FeatureSample {
+                revision: revision.name.clone(),
+                owner: feature.owner.clone(),
+                files: count(stats.and_then(|stats| stats.files_count)),
+                lines: count(stats.and_then(|stats| stats.lines_count)),
+                todos: count(stats.and_then(|stats| stats.todos_count)),
+                dependencies: feature.dependencies.len(),
+            });
+        }
+        revisions.push(revision);
+    }
+
+    Timeline {
+        revisions,
+        features: features.into_values().collect(),
+    }
+}
+
+/// One block per feature with a line per revision
+pub fn format_text(timeline: &Timeline) -> String {
+    let mut output = String::new();
+
+    for feature in &timeline.features {
+        let _ = writeln!(output, "{} ({})", feature.name, feature.path);
+        let _ = writeln!(
+            output,
+            "  {:<20} {:>6} {:>8} {:>6} {:>6}  OWNER",
+            "REVISION", "FILES", "LINES", "TODOS", "DEPS"
+        );
+        for sample in &feature.samples {
+            let _ = writeln!(
+                output,
+                "  {:<20} {:>6} {:>8} {:>6} {:>6}  {}",
+                sample.revision,
+                sample.files,
+                sample.lines,
+                sample.todos,
+                sample.dependencies,
+                sample.owner
+            );
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_fixture::commit_file;
+    use tempfile::TempDir;
+
+    fn revision(name: &str) -> SampledRevision {
+        SampledRevision {
+            name: name.to_string(),
+            commit: format!("{}-commit", name),
+            date: "2025-01-01 00:00:00".to_string(),
+        }
+    }
+
+    fn feature(path: &str, owner: &str, features: Vec<Feature>) -> Feature {
+        Feature {
+            owner: owner.to_string(),
+            features,
+            ..Feature::at(path)
+        }
+    }
+
+    #[test]
+    fn test_build_timeline() {
+        let timeline = build_timeline(vec![
+            (
+                revision("v1"),
+                vec![feature("features/auth", "team-a", Vec::new())],
+            ),
+            (
+                revision("v2"),
+                vec![feature(
+                    "features/auth",
+                    "team-b",
+                    vec![feature("features/auth/features/sso", "team-b", Vec::new())],
+                )],
+            ),
+        ]);
+
+        assert_eq!(timeline.revisions.len(), 2);
+        let paths: Vec<&str> = timeline
+            .features
+            .iter()
+            .map(|feature| feature.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["features/auth", "features/auth/features/sso"]);
+
+        let owners: Vec<(&str, &str)> = timeline.features[0]
+            .samples
+            .iter()
+            .map(|sample| (sample.revision.as_str(), sample.owner.as_str()))
+            .collect();
+        assert_eq!(owners, vec![("v1", "team-a"), ("v2", "team-b")]);
+        assert_eq!(timeline.features[1].samples[0].revision, "v2");
+        assert!(
+            format_text(&timeline)
+                .contains("  v1                        0        0      0      0  team-a\n")
+        );
+    }
+
+    #[test]
+    fn test_sample_revisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let day = 24 * 60 * 60;
+
+        let first = commit_file(&repo, "a.txt", "a", 1_000_000);
+        let second = commit_file(&repo, "a.txt", "a", 1_000_000 + 3 * day);
+        let third = commit_file(&repo, "a.txt", "a", 1_000_000 + 10 * day);
+        commit_file(&repo, "a.txt", "a", 1_000_000 + 30 * day);
+        let fifth = commit_file(&repo, "a.txt", "a", 1_000_000 + 31 * day);
+        for (name, oid) in [("v1", first), ("v2", third), ("v3", fifth)] {
+            let object = repo.find_object(oid, None).unwrap();
+            repo.tag_lightweight(name, &object, false).unwrap();
+        }
+
+        let commits = |samples: Vec<SampledRevision>| -> Vec<String> {
+            samples.into_iter().map(|sample| sample.commit).collect()
+        };
+
+        let samples = sample_revisions(temp_dir.path(), None, Sampling::Weeks(1), 10).unwrap();
+        assert_eq!(
+            commits(samples),
+            vec![second.to_string(), third.to_string(), fifth.to_string()]
+        );
+
+        let samples = sample_revisions(temp_dir.path(), None, Sampling::Weeks(1), 2).unwrap();
+        assert_eq!(commits(samples), vec![third.to_string(), fifth.to_string()]);
+
+        let samples = sample_revisions(temp_dir.path(), None, Sampling::Tags, 10).unwrap();
+        let tags: Vec<String> = samples.into_iter().map(|sample| sample.name).collect();
+        assert_eq!(tags, vec!["v1", "v2", "v3"]);
+
+        // Tags made after the last revision are left out
+        let samples = sample_revisions(temp_dir.path(), Some("v2"), Sampling::Tags, 10).unwrap();
+        let tags: Vec<String> = samples.into_iter().map(|sample| sample.name).collect();
+        assert_eq!(tags, vec!["v1", "v2"]);
+    }
+}
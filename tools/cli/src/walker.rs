@@ -0,0 +1,679 @@
+//! Shared directory walker
+//!
+//! The scanner, the dependency resolver, the import detector and the metadata detector all
+//! read the content of the scanned folder from a single [`FileTree`], so they agree on which
+//! files exist. The walk honors `.gitignore`, `.ignore` and git exclude files, skips the
+//! `skip_dirs` folder names and the `exclude` globs of the [`ScanSettings`].
+//!
+//! Files are read through the [`Source`] of the tree: the working directory, or a git
+//! revision read from the object database without checking it out.
+
+use anyhow::{Context, Result};
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{Match, WalkBuilder};
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::ScanSettings;
+
+/// The files and directories of a scanned folder that are not ignored
+pub struct FileTree {
+    root: PathBuf,
+    /// Children of each directory, sorted by name
+    children: HashMap<PathBuf, Vec<PathBuf>>,
+    files: BTreeSet<PathBuf>,
+    source: Source,
+}
+
+impl FileTree {
+    /// Walk `root` once, collecting every entry that is not ignored
+    ///
+    /// Paths are built by joining entry names to `root`, exactly like `fs::read_dir` does,
+    /// so they can be compared with paths computed elsewhere from the same root.
+    pub fn walk(root: &Path, settings: &ScanSettings) -> Result<Self> {
+        if !root.is_dir() {
+            anyhow::bail!("could not read directory `{}`", root.display());
+        }
+
+        let overrides = exclude_overrides(root, settings)?;
+
+        let respect_ignore_files = settings.respect_gitignore;
+        let filter_settings = settings.clone();
+
+        let walker = WalkBuilder::new(root)
+            .hidden(false)
+            .parents(respect_ignore_files)
+            .ignore(respect_ignore_files)
+            .git_ignore(respect_ignore_files)
+            .git_global(respect_ignore_files)
+            .git_exclude(respect_ignore_files)
+            .require_git(false)
+            .follow_links(true)
+            .overrides(overrides)
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+                !(is_dir
+                    && entry.depth() > 0
+                    && filter_settings.is_skipped_dir(&entry.file_name().to_string_lossy()))
+            })
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        let mut tree = FileTree::empty(root, Source::WorkingDirectory);
+
+        // Unreadable entries are skipped, like the previous per-module walkers did
+        for entry in walker.flatten() {
+            if entry.depth() == 0 {
+                continue;
+            }
+
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            tree.insert(entry.path().to_path_buf(), is_dir);
+        }
+
+        Ok(tree)
+    }
+
+    /// Walk `root` as it is in a git revision (commit, tag or branch), without checking it out
+    ///
+    /// The ignore files and the `exclude` globs apply as they would to a checkout of the
+    /// revision, except the git exclude files which belong to the local clone. Symbolic links
+    /// and submodules are left out.
+    pub fn walk_revision(root: &Path, revision: &str, settings: &ScanSettings) -> Result<Self> {
+        let revision_tree = RevisionTree::open(root, revision)?;
+        let root_in_repo = revision_tree
+            .path_in_repository(root)
+            .filter(|path| revision_tree.dirs.contains_key(path))
+            .with_context(|| {
+                format!(
+                    "could not find directory `{}` in revision `{}`",
+                    root.display(),
+                    revision
+                )
+            })?;
+
+        let mut walk = RevisionWalk {
+            revision_tree: &revision_tree,
+            settings,
+            overrides: exclude_overrides(root, settings)?,
+            ignores: Vec::new(),
+            tree: FileTree::empty(root, Source::WorkingDirectory),
+        };
+
+        if settings.respect_gitignore {
+            // Ignore files of the folders above the scanned one apply too, outermost first
+            let ancestors: Vec<&Path> = root_in_repo.ancestors().skip(1).collect();
+            for dir in ancestors.into_iter().rev() {
+                walk.ignores.extend(revision_tree.ignore_files(dir));
+            }
+        }
+        walk.visit(&root_in_repo, root);
+
+        let mut tree = walk.tree;
+        tree.source = Source::Revision(Box::new(revision_tree));
+        Ok(tree)
+    }
+
+    fn empty(root: &Path, source: Source) -> Self {
+        let mut tree = FileTree {
+            root: root.to_path_buf(),
+            children: HashMap::new(),
+            files: BTreeSet::new(),
+            source,
+        };
+        tree.children.insert(root.to_path_buf(), Vec::new());
+        tree
+    }
+
+    /// Add an entry, after its parent directory
+    fn insert(&mut self, path: PathBuf, is_dir: bool) {
+        if is_dir {
+            self.children.entry(path.clone()).or_default();
+        } else {
+            self.files.insert(path.clone());
+        }
+
+        if let Some(parent) = path.parent() {
+            self.children
+                .entry(parent.to_path_buf())
+                .or_default()
+                .push(path);
+        }
+    }
+
+    /// The folder this tree was walked from
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the files of this tree are read from
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Entries directly inside `dir`, sorted by name (empty if `dir` is unknown or ignored)
+    pub fn read_dir(&self, dir: &Path) -> &[PathBuf] {
+        self.children.get(dir).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Check if `path` is a directory that was not ignored
+    pub fn is_dir(&self, path: &Path) -> bool {
+        self.children.contains_key(path)
+    }
+
+    /// Check if `path` is a file that was not ignored
+    pub fn is_file(&self, path: &Path) -> bool {
+        self.files.contains(path)
+    }
+
+    /// All directories of the tree, the root included, in no particular order
+    pub fn dirs(&self) -> impl Iterator<Item = &PathBuf> {
+        self.children.keys()
+    }
+
+    /// All files of the tree, sorted by path
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter()
+    }
+}
+
+fn exclude_overrides(root: &Path, settings: &ScanSettings) -> Result<Override> {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &settings.exclude {
+        // Override globs are a whitelist unless negated
+        overrides
+            .add(&format!("!{}", glob))
+            .with_context(|| format!("invalid exclude glob `{}`", glob))?;
+    }
+    Ok(overrides.build()?)
+}
+
+/// State of the walk of a folder in a git revision
+struct RevisionWalk<'a> {
+    revision_tree: &'a RevisionTree,
+    settings: &'a ScanSettings,
+    overrides: Override,
+    /// Ignore files of the folders being walked, outermost first
+    ignores: Vec<Gitignore>,
+    tree: FileTree,
+}
+
+impl RevisionWalk<'_> {
+    /// Add the entries of a directory, `dir_in_repo` being its path relative to the repository
+    fn visit(&mut self, dir_in_repo: &Path, dir: &Path) {
+        let ignores_count = self.ignores.len();
+        if self.settings.respect_gitignore {
+            self.ignores
+                .extend(self.revision_tree.ignore_files(dir_in_repo));
+        }
+
+        for name in &self.revision_tree.dirs[dir_in_repo] {
+            let entry_in_repo = dir_in_repo.join(name);
+            let path = dir.join(name);
+            let is_dir = self.revision_tree.dirs.contains_key(&entry_in_repo);
+
+            if (is_dir && self.settings.is_skipped_dir(&name.to_string_lossy()))
+                || self.overrides.matched(&path, is_dir).is_ignore()
+                || self.is_ignored(&entry_in_repo, is_dir)
+            {
+                continue;
+            }
+
+            self.tree.insert(path.clone(), is_dir);
+            if is_dir {
+                self.visit(&entry_in_repo, &path);
+            }
+        }
+
+        self.ignores.truncate(ignores_count);
+    }
+
+    fn is_ignored(&self, path_in_repo: &Path, is_dir: bool) -> bool {
+        // The innermost ignore file matching the path decides, like in a checkout
+        for ignore in self.ignores.iter().rev() {
+            match ignore.matched(path_in_repo, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// Where the files of a scan are read from
+///
+/// Paths are the ones of the [`FileTree`], relative to the current directory or absolute,
+/// whatever the source.
+pub enum Source {
+    /// The files on disk
+    WorkingDirectory,
+    /// The files of a git revision
+    Revision(Box<RevisionTree>),
+}
+
+impl Source {
+    /// Id of the scanned commit, `None` for the working directory
+    pub fn revision(&self) -> Option<&str> {
+        match self {
+            Source::WorkingDirectory => None,
+            Source::Revision(tree) => Some(&tree.commit),
+        }
+    }
+
+    pub fn is_file(&self, path: &Path) -> bool {
+        match self {
+            Source::WorkingDirectory => path.is_file(),
+            Source::Revision(tree) => tree.file_id(path).is_some(),
+        }
+    }
+
+    pub fn is_dir(&self, path: &Path) -> bool {
+        match self {
+            Source::WorkingDirectory => path.is_dir(),
+            Source::Revision(tree) => tree.children(path).is_some(),
+        }
+    }
+
+    pub fn exists(&self, path: &Path) -> bool {
+        match self {
+            Source::WorkingDirectory => path.exists(),
+            Source::Revision(_) => self.is_file(path) || self.is_dir(path),
+        }
+    }
+
+    pub fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self {
+            Source::WorkingDirectory => fs::read_to_string(path),
+            Source::Revision(tree) => tree.read_to_string(path),
+        }
+    }
+
+    /// Entries of a directory, in no particular order
+    pub fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        match self {
+            Source::WorkingDirectory => fs::read_dir(path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect(),
+            Source::Revision(tree) => tree
+                .children(path)
+                .map(|names| names.iter().map(|name| path.join(name)).collect())
+                .ok_or_else(|| io::ErrorKind::NotFound.into()),
+        }
+    }
+
+    /// Absolute path without `.` nor `..`, a revision's files being placed in the working
+    /// directory of the repository
+    pub fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        match self {
+            Source::WorkingDirectory => fs::canonicalize(path),
+            Source::Revision(tree) => tree.canonicalize(path),
+        }
+    }
+}
+
+/// Files and directories of a git revision, read from the object database
+pub struct RevisionTree {
+    /// Id of the commit the revision resolved to
+    commit: String,
+    repo: Mutex<Repository>,
+    current_dir: PathBuf,
+    /// Root of the repository, without `.` nor `..` but with symbolic links unresolved
+    root: PathBuf,
+    /// Root of the repository with symbolic links resolved
+    workdir: PathBuf,
+    /// Blob of every file, keyed by path relative to the repository root
+    files: HashMap<PathBuf, Oid>,
+    /// Entry names of every directory, sorted, keyed by path relative to the repository root
+    dirs: HashMap<PathBuf, Vec<OsString>>,
+}
+
+impl RevisionTree {
+    /// Read the tree of `revision` in the repository containing `path`
+    fn open(path: &Path, revision: &str) -> Result<Self> {
+        let (repo, path_in_repo) = discover_repository(path)?;
+        let workdir = repo
+            .workdir()
+            .context("repository has no working directory")?
+            .canonicalize()?;
+
+        // The repository root is found from the scanned path as typed, so that the paths of
+        // the tree can be mapped to the repository without touching the disk
+        let current_dir = std::env::current_dir()?;
+        let root = normalize(&current_dir.join(path))
+            .ancestors()
+            .nth(path_in_repo.components().count())
+            .context("path is not within repository")?
+            .to_path_buf();
+
+        let commit = repo
+            .revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("could not resolve revision `{}`", revision))?;
+        let commit_id = commit.id().to_string();
+        let tree = commit.tree()?;
+
+        let mut files = HashMap::new();
+        let mut dirs: HashMap<PathBuf, Vec<OsString>> = HashMap::new();
+        dirs.insert(PathBuf::new(), Vec::new());
+
+        tree.walk(TreeWalkMode::PreOrder, |parent, entry| {
+            let Some(name) = entry.name() else {
+                return TreeWalkResult::Skip;
+            };
+            let parent = PathBuf::from(parent.trim_end_matches('/'));
+            let path = parent.join(name);
+
+            match entry.kind() {
+                Some(ObjectType::Tree) => {
+                    dirs.entry(path).or_default();
+                }
+                // Symbolic links have their target as content
+                Some(ObjectType::Blob) if entry.filemode() != 0o120000 => {
+                    files.insert(path, entry.id());
+                }
+                _ => return TreeWalkResult::Skip,
+            }
+
+            dirs.entry(parent).or_default().push(name.into());
+            TreeWalkResult::Ok
+        })?;
+
+        for names in dirs.values_mut() {
+            names.sort();
+        }
+
+        drop(tree);
+        drop(commit);
+        Ok(Self {
+            commit: commit_id,
+            repo: Mutex::new(repo),
+            current_dir,
+            root,
+            workdir,
+            files,
+            dirs,
+        })
+    }
+
+    /// Path relative to the repository root, `None` outside of the repository
+    fn path_in_repository(&self, path: &Path) -> Option<PathBuf> {
+        let path = normalize(&self.current_dir.join(path));
+        path.strip_prefix(&self.root)
+            .or_else(|_| path.strip_prefix(&self.workdir))
+            .ok()
+            .map(Path::to_path_buf)
+    }
+
+    fn file_id(&self, path: &Path) -> Option<Oid> {
+        self.files.get(&self.path_in_repository(path)?).copied()
+    }
+
+    fn children(&self, path: &Path) -> Option<&[OsString]> {
+        self.dirs
+            .get(&self.path_in_repository(path)?)
+            .map(Vec::as_slice)
+    }
+
+    fn read_blob(&self, id: Oid) -> io::Result<String> {
+        let repo = self.repo.lock().unwrap();
+        let blob = repo.find_blob(id).map_err(io::Error::other)?;
+        String::from_utf8(blob.content().to_vec())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let id = self.file_id(path).ok_or(io::ErrorKind::NotFound)?;
+        self.read_blob(id)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let path_in_repo = self
+            .path_in_repository(path)
+            .ok_or(io::ErrorKind::NotFound)?;
+        if !self.files.contains_key(&path_in_repo) && !self.dirs.contains_key(&path_in_repo) {
+            return Err(io::ErrorKind::NotFound.into());
+        }
+        Ok(self.workdir.join(path_in_repo))
+    }
+
+    /// `.gitignore` then `.ignore` of a directory, the latter taking precedence
+    fn ignore_files(&self, dir_in_repo: &Path) -> Vec<Gitignore> {
+        [".gitignore", ".ignore"]
+            .iter()
+            .filter_map(|name| {
+                let id = self.files.get(&dir_in_repo.join(name))?;
+                let content = self.read_blob(*id).ok()?;
+
+                let mut builder = GitignoreBuilder::new(dir_in_repo);
+                for line in content.lines() {
+                    // Invalid lines are skipped, like the walker of the working directory does
+                    let _ = builder.add_line(None, line);
+                }
+                builder.build().ok()
+            })
+            .collect()
+    }
+}
+
+/// Repository containing `path`, and `path` relative to its working directory
+///
+/// `path` does not need to exist, so that folders deleted since a revision can be scanned in
+/// it: the repository is discovered from the closest existing ancestor of the path, and the
+/// rest of the path is resolved lexically.
+pub fn discover_repository(path: &Path) -> Result<(Repository, PathBuf)> {
+    let absolute_path = normalize(&std::env::current_dir()?.join(path));
+    let existing_path = absolute_path
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .unwrap_or(&absolute_path);
+    let missing_path = absolute_path.strip_prefix(existing_path)?;
+
+    let repo = Repository::discover(existing_path)
+        .with_context(|| format!("failed to discover git repository at `{}`", path.display()))?;
+    let workdir = repo
+        .workdir()
+        .context("repository has no working directory")?
+        .canonicalize()?;
+    let path_in_repo = existing_path
+        .canonicalize()?
+        .join(missing_path)
+        .strip_prefix(&workdir)
+        .context("path is not within repository")?
+        .to_path_buf();
+
+    Ok((repo, path_in_repo))
+}
+
+/// Remove `.` and `..` from a path without touching the disk
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_fixture::commit_all;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_files(root: &Path, files: &[&str]) {
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "content").unwrap();
+        }
+    }
+
+    fn relative_files(tree: &FileTree) -> Vec<String> {
+        tree.files()
+            .map(|f| {
+                f.strip_prefix(tree.root())
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_walk_honors_gitignore_and_skip_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        create_files(
+            temp_dir.path(),
+            &[
+                ".gitignore",
+                "src/features/a/index.ts",
+                "src/generated/api.ts",
+                "node_modules/lib/index.js",
+            ],
+        );
+        fs::write(temp_dir.path().join(".gitignore"), "generated/\n").unwrap();
+
+        let tree = FileTree::walk(temp_dir.path(), &ScanSettings::default()).unwrap();
+
+        assert_eq!(
+            relative_files(&tree),
+            vec![".gitignore", "src/features/a/index.ts"]
+        );
+        assert!(tree.is_dir(&temp_dir.path().join("src/features")));
+        assert!(!tree.is_dir(&temp_dir.path().join("src/generated")));
+    }
+
+    #[test]
+    fn test_walk_honors_exclude_globs() {
+        let temp_dir = TempDir::new().unwrap();
+        create_files(
+            temp_dir.path(),
+            &[
+                "src/features/a/index.ts",
+                "src/features/a/index.gen.ts",
+                "vendored/lib.ts",
+            ],
+        );
+
+        let mut settings = ScanSettings::default();
+        settings.exclude = vec!["*.gen.ts".to_string(), "vendored/".to_string()];
+        let tree = FileTree::walk(temp_dir.path(), &settings).unwrap();
+
+        assert_eq!(relative_files(&tree), vec!["src/features/a/index.ts"]);
+    }
+
+    #[test]
+    fn test_walk_without_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        create_files(temp_dir.path(), &[".ignore", "generated/api.ts"]);
+        fs::write(temp_dir.path().join(".ignore"), "generated/\n").unwrap();
+
+        let mut settings = ScanSettings::default();
+        settings.respect_gitignore = false;
+        let tree = FileTree::walk(temp_dir.path(), &settings).unwrap();
+
+        assert_eq!(relative_files(&tree), vec![".ignore", "generated/api.ts"]);
+    }
+
+    #[test]
+    fn test_walk_revision_reads_the_committed_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let repo = Repository::init(root).unwrap();
+        create_files(
+            root,
+            &[
+                ".gitignore",
+                "src/features/a/index.ts",
+                "src/features/a/index.gen.ts",
+                "src/generated/api.ts",
+                "src/vendored/lib.ts",
+            ],
+        );
+        fs::write(root.join(".gitignore"), "generated/\n").unwrap();
+        let commit = commit_all(&repo, "commit", 1_000).to_string();
+
+        // The working directory moves on after the commit
+        fs::write(root.join("src/features/a/index.ts"), "changed").unwrap();
+        create_files(root, &["src/features/b/index.ts"]);
+        fs::remove_dir_all(root.join("src/vendored")).unwrap();
+
+        let mut settings = ScanSettings::default();
+        settings.exclude = vec!["*.gen.ts".to_string()];
+        let tree = FileTree::walk_revision(root, &commit, &settings).unwrap();
+
+        assert_eq!(
+            relative_files(&tree),
+            vec![
+                ".gitignore",
+                "src/features/a/index.ts",
+                "src/vendored/lib.ts"
+            ]
+        );
+        assert_eq!(tree.source().revision(), Some(commit.as_str()));
+
+        let source = tree.source();
+        let vendored = root.join("src/vendored/lib.ts");
+        assert!(source.is_file(&vendored));
+        assert_eq!(source.read_to_string(&vendored).unwrap(), "content");
+        assert_eq!(
+            source
+                .read_to_string(&root.join("src/features/a/index.ts"))
+                .unwrap(),
+            "content"
+        );
+        assert!(!source.exists(&root.join("src/features/b")));
+        assert!(source.is_dir(&root.join("src/features/../vendored")));
+        assert_eq!(
+            source.canonicalize(&root.join("src/./vendored")).unwrap(),
+            root.canonicalize().unwrap().join("src/vendored")
+        );
+
+        // Ignore files above the scanned folder still apply
+        let nested_tree = FileTree::walk_revision(&root.join("src"), &commit, &settings).unwrap();
+        assert_eq!(
+            relative_files(&nested_tree),
+            vec!["features/a/index.ts", "vendored/lib.ts"]
+        );
+
+        assert!(FileTree::walk_revision(&root.join("src/features/b"), &commit, &settings).is_err());
+
+        // Folders deleted since the revision are read from it
+        let deleted_tree =
+            FileTree::walk_revision(&root.join("src/vendored"), &commit, &settings).unwrap();
+        assert_eq!(relative_files(&deleted_tree), vec!["lib.ts"]);
+        assert!(
+            deleted_tree
+                .source()
+                .is_file(&root.join("src/vendored/lib.ts"))
+        );
+    }
+
+    #[test]
+    fn test_read_dir_is_sorted() {
+        let temp_dir = TempDir::new().unwrap();
+        create_files(temp_dir.path(), &["b.ts", "a/x.ts", "c.ts"]);
+
+        let tree = FileTree::walk(temp_dir.path(), &ScanSettings::default()).unwrap();
+        let names: Vec<_> = tree
+            .read_dir(temp_dir.path())
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+
+        assert_eq!(names, vec!["a", "b.ts", "c.ts"]);
+    }
+}
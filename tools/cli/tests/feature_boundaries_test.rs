@@ -0,0 +1,235 @@
+//! Integration tests checking that every subsystem agrees on feature boundaries
+//!
+//! The same fixture is read by the scanner, the dependency file map, the coverage mapper
+//! and the `owner` and `owners` subcommands, and each of them must attach every file to the
+//! same feature.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use tempfile::TempDir;
+
+use features_cli::cache::ScanCache;
+use features_cli::config::ScanSettings;
+use features_cli::coverage_parser::{CoverageStats, map_coverage_to_features};
+use features_cli::dependency_resolver::{build_file_to_feature_map, collect_feature_info};
+use features_cli::models::Feature;
+use features_cli::scan::{ScanConfig, scan_features};
+use features_cli::walker::FileTree;
+
+/// Files of the fixture and the feature each of them belongs to
+const FILES: &[(&str, Option<&str>)] = &[
+    // Direct subfolder of the `features` container
+    ("src/features/auth/index.ts", Some("src/features/auth")),
+    ("src/features/auth/docs/guide.md", Some("src/features/auth")),
+    // Nested container
+    (
+        "src/features/auth/features/sso/login.ts",
+        Some("src/features/auth/features/sso"),
+    ),
+    // FEATURES.toml outside of any container, with a nested lowercase features.toml
+    ("lib/billing/FEATURES.toml", Some("lib/billing")),
+    ("lib/billing/invoice.ts", Some("lib/billing")),
+    (
+        "lib/billing/refunds/features.toml",
+        Some("lib/billing/refunds"),
+    ),
+    ("lib/billing/refunds/refund.ts", Some("lib/billing/refunds")),
+    // README front matter
+    ("tools/ext/README.md", Some("tools/ext")),
+    ("tools/ext/main.ts", Some("tools/ext")),
+    // Not part of any feature
+    ("lib/shared/util.ts", None),
+];
+
+fn create_fixture() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+
+    for (file, _) in FILES {
+        let path = temp_dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "export const value = 1;\n").unwrap();
+    }
+
+    fs::write(
+        temp_dir.path().join("lib/billing/FEATURES.toml"),
+        "name = \"Billing\"\nowner = \"billing-team\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("lib/billing/refunds/features.toml"),
+        "name = \"Refunds\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("tools/ext/README.md"),
+        "---\nfeature: true\n---\n# Extension\n",
+    )
+    .unwrap();
+
+    temp_dir
+}
+
+fn scan(base: &Path) -> Vec<Feature> {
+    let config = ScanConfig::new(base).skip_changes(true);
+    scan_features(base, config).unwrap()
+}
+
+fn collect_paths(features: &[Feature], paths: &mut BTreeSet<String>) {
+    for feature in features {
+        paths.insert(feature.path.clone());
+        collect_paths(&feature.features, paths);
+    }
+}
+
+#[test]
+fn test_scanner_detects_every_feature_marker() {
+    let temp_dir = create_fixture();
+    let features = scan(temp_dir.path());
+
+    let mut paths = BTreeSet::new();
+    collect_paths(&features, &mut paths);
+
+    let expected: BTreeSet<String> = FILES
+        .iter()
+        .filter_map(|(_, feature)| feature.map(String::from))
+        .collect();
+    assert_eq!(paths, expected);
+}
+
+#[test]
+fn test_dependency_map_agrees_with_scanner() {
+    let temp_dir = create_fixture();
+    let base = temp_dir.path();
+    let features = scan(base);
+
+    let mut feature_info = Vec::new();
+    collect_feature_info(&features, None, &mut feature_info);
+
+    let settings = ScanSettings::default();
+    let tree = FileTree::walk(base, &settings).unwrap();
+    let cache = ScanCache::in_memory(&tree);
+    let file_to_feature = build_file_to_feature_map(&feature_info, base, &tree, &settings, &cache);
+
+    for (file, expected) in FILES {
+        let canonical = base.join(file).canonicalize().unwrap();
+        assert_eq!(
+            file_to_feature.get(&canonical).map(String::as_str),
+            *expected,
+            "dependency map disagrees for {}",
+            file
+        );
+    }
+}
+
+#[test]
+fn test_coverage_mapper_agrees_with_scanner() {
+    let temp_dir = create_fixture();
+    let base = temp_dir.path();
+    let features = scan(base);
+
+    for (file, expected) in FILES {
+        let coverage_map = HashMap::from([(file.to_string(), CoverageStats::default())]);
+        let feature_coverage = map_coverage_to_features(&features, coverage_map, base);
+        let mapped: Vec<&str> = feature_coverage.keys().map(String::as_str).collect();
+
+        assert_eq!(
+            mapped,
+            expected.iter().copied().collect::<Vec<_>>(),
+            "coverage mapper disagrees for {}",
+            file
+        );
+    }
+}
+
+#[test]
+fn test_owner_lookup_agrees_with_scanner() {
+    let temp_dir = create_fixture();
+    let base = temp_dir.path();
+
+    for (file, expected) in FILES {
+        let output = Command::new(env!("CARGO_BIN_EXE_features"))
+            .arg("owner")
+            .arg(base.join(file))
+            .arg(base)
+            .args(["--skip-changes", "--json"])
+            .output()
+            .unwrap();
+
+        let feature_path = if output.status.success() {
+            let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            json["feature_path"].as_str().map(String::from)
+        } else {
+            None
+        };
+
+        assert_eq!(
+            feature_path.as_deref(),
+            *expected,
+            "owner lookup disagrees for {}",
+            file
+        );
+    }
+}
+
+#[test]
+fn test_batch_owner_lookup_agrees_with_scanner() {
+    let temp_dir = create_fixture();
+    let base = temp_dir.path();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_features"))
+        .arg("owners")
+        .arg(base)
+        .arg("--json")
+        .current_dir(base)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Paths relative to the current directory, plus one that no longer exists
+    let mut input: String = FILES
+        .iter()
+        .map(|(file, _)| format!("{}\n", file))
+        .collect();
+    input.push_str("src/features/auth/deleted.ts\n");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let mut feature_paths = HashMap::new();
+    let groups = report["owners"].as_array().unwrap();
+    let entries = groups
+        .iter()
+        .flat_map(|group| group["paths"].as_array().unwrap())
+        .chain(report["unowned"].as_array().unwrap());
+    for entry in entries {
+        feature_paths.insert(
+            entry["path"].as_str().unwrap().to_string(),
+            entry["feature_path"].as_str().map(String::from),
+        );
+    }
+
+    for (file, expected) in FILES {
+        assert_eq!(
+            feature_paths[*file].as_deref(),
+            *expected,
+            "batch owner lookup disagrees for {}",
+            file
+        );
+    }
+    assert_eq!(
+        feature_paths["src/features/auth/deleted.ts"].as_deref(),
+        Some("src/features/auth")
+    );
+}
@@ -1,4 +1,4 @@
-//! Integration tests for --find-owner CLI feature
+//! Integration tests for the `owner` subcommand (and its deprecated `--find-owner` flag)
 //!
 //! This test suite verifies that the CLI correctly finds owners for files and folders,
 //! including inheritance from parent features when the immediate feature has an unknown owner.
@@ -393,3 +393,113 @@ fn test_find_owner_path_not_in_any_feature() {
         error
     );
 }
+
+#[test]
+fn test_owner_subcommand() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let test_base = manifest_dir.join("../../examples/tests-skip-changes/src");
+    let target_path = test_base.join("features/feature-1/features/feature-2");
+
+    if !test_base.exists() {
+        println!(
+            "Skipping test - test path does not exist: {}",
+            test_base.display()
+        );
+        return;
+    }
+
+    let result = run_cli(&[
+        "owner",
+        target_path.to_str().unwrap(),
+        test_base.to_str().unwrap(),
+        "--skip-changes",
+    ]);
+
+    assert!(result.is_ok(), "CLI execution failed: {:?}", result.err());
+
+    let output = result.unwrap();
+    assert!(
+        output.contains("Owner: team1 (inherited)"),
+        "Output should contain 'Owner: team1 (inherited)', got: {}",
+        output
+    );
+}
+
+#[test]
+fn test_legacy_modes_keep_their_precedence() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let test_base = manifest_dir.join("../../examples/tests-skip-changes/src");
+    let target_path = test_base.join("features/feature-1");
+
+    if !test_base.exists() {
+        println!(
+            "Skipping test - test path does not exist: {}",
+            test_base.display()
+        );
+        return;
+    }
+
+    let output = Command::new(get_binary_path())
+        .args([
+            test_base.to_str().unwrap(),
+            "--find-owner",
+            target_path.to_str().unwrap(),
+            "--check",
+        ])
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(output.status.success(), "--find-owner should still run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stdout.contains("Owner: team1"),
+        "--find-owner should take precedence over --check, got: {}",
+        stdout
+    );
+    assert!(
+        stderr.contains("--check ignored in favor of --find-owner"),
+        "Ignored modes should be reported, got: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_owner_lookup_from_a_subdirectory() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let test_base = manifest_dir.join("../../examples/tests-skip-changes/src");
+
+    if !test_base.exists() {
+        println!(
+            "Skipping test - test path does not exist: {}",
+            test_base.display()
+        );
+        return;
+    }
+
+    // Relative targets are relative to the current directory, not to the repository root
+    for args in [
+        vec!["owner", "features/feature-1", ".", "--skip-changes"],
+        vec![".", "--find-owner", "features/feature-1", "--skip-changes"],
+    ] {
+        let output = Command::new(get_binary_path())
+            .args(&args)
+            .current_dir(&test_base)
+            .output()
+            .expect("Failed to execute binary");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "{:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(
+            stdout.contains("Owner: team1"),
+            "{:?} should find the owner, got: {}",
+            args,
+            stdout
+        );
+    }
+}